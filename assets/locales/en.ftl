@@ -0,0 +1,170 @@
+game-title = Tile matching
+
+## Главное меню
+
+menu-continue = Continue
+menu-campaign = Campaign
+menu-watch-replay = Watch last replay
+menu-editor = Level editor
+menu-settings = Settings
+menu-high-scores = High Scores
+menu-quit = Quit
+menu-back = Back
+
+mode-classic = Classic
+mode-moves = Moves
+mode-timed = Timed
+mode-versus = Versus
+mode-race = Race
+mode-daily = Daily challenge
+mode-campaign = Campaign
+
+## Настройки
+
+on = On
+off = Off
+settings-fullscreen = Fullscreen: { $value }
+settings-vsync = VSync: { $value }
+volume-master = Master volume: { $percent }%
+volume-music = Music volume: { $percent }%
+volume-effects = Effects volume: { $percent }%
+settings-effects = Effects: { $value }
+settings-theme = Theme: { $value }
+settings-animation-speed = Animation: { $percent }%
+settings-hint-delay = Hint: { $seconds ->
+        [0] on H only
+       *[other] after { $seconds } s
+    }
+settings-palette = Colours: { $value }
+palette-theme = Theme
+palette-deuteranopia = Deuteranopia
+palette-protanopia = Protanopia
+palette-tritanopia = Tritanopia
+settings-glyphs = Form glyphs: { $value }
+settings-screen-reader = Screen reader: { $value }
+settings-form-cues = Form sounds: { $value }
+settings-language = Language: { $value }
+
+## Партия
+
+pause-title = Paused
+pause-resume = Resume
+pause-restart = Restart
+pause-main-menu = Main menu
+game-over-title = Game over
+versus-winner = { $player } wins!
+versus-draw = Draw!
+score = Score: { $score }
+daily-best = Best today: { $score }
+daily-streak = Streak: { $days ->
+        [one] { $days } day
+       *[other] { $days } days
+    }
+hud-moves = Moves: { $moves }
+hud-time = Time: { $seconds }
+hud-undo = Undo (Ctrl+Z): { $undos }
+hud-autoplay = Autoplay (A): on
+player-one = Player 1
+player-two = Player 2
+hud-player-score = { $player }: { $score }
+hud-turn = { $player }'s turn, round { $round }/{ $rounds }
+hud-target = Target: { $score }
+hud-daily = Daily challenge { $date }
+hud-collect = { $form }: { $collected }/{ $count }
+net-you = You are { $player }
+net-disconnected = Opponent disconnected
+net-desync = Boards diverged on turn { $turn }
+replay-status = Replay { $speed }x
+replay-paused = { replay-status } (paused)
+replay-help =
+    Speed: - / =
+    Pause: Space
+
+## Кампания
+
+campaign-title = Campaign
+campaign-unavailable = Campaign failed to load
+campaign-loading = Loading the campaign…
+campaign-level-loading = { $number }. Loading…
+campaign-broken = { $number }. Failed to load
+campaign-level = { $number }. { $name }: { $stars }/{ $max } stars
+campaign-locked = { $number }. Locked
+campaign-stars = Level cleared: { $stars }/{ $max } stars
+campaign-failed = Level failed
+
+## Редактор уровней
+
+editor-name = Name: { $name }
+editor-rename = Rename
+editor-new = New
+editor-previous = <
+editor-next = >
+editor-undo = Undo
+editor-redo = Redo
+editor-less = −
+editor-more = +
+editor-width = Width: { $width }
+editor-height = Height: { $height }
+editor-moves = Moves: { $moves ->
+        [0] unlimited
+       *[other] { $moves }
+    }
+editor-score-goal = Score goal: { $score ->
+        [0] none
+       *[other] { $score }
+    }
+editor-collect-goal = Collect: { $count } { $form }
+editor-collect-pick = Collect: pick a tile
+editor-test-play = Test
+editor-save = Save
+editor-saved = Saved to { $path }
+editor-save-failed = Failed to save: { $error }
+editor-return = Back to editor
+
+## Рекорды
+
+new-record-title = New record!
+enter-name = Enter your name:
+high-scores-title = High Scores
+high-scores-standard = Standard board
+header-rank = #
+header-name = Name
+header-score = Score
+header-moves = Moves
+header-date = Date
+no-records = No records yet
+
+## Озвучка
+
+form-circle = { $count ->
+    [one] circle
+   *[other] circles
+}
+form-square = { $count ->
+    [one] square
+   *[other] squares
+}
+form-triangle = { $count ->
+    [one] triangle
+   *[other] triangles
+}
+form-rhombus = { $count ->
+    [one] rhombus
+   *[other] rhombuses
+}
+form-annulus = { $count ->
+    [one] ring
+   *[other] rings
+}
+announce-cell = Row { $row }, column { $column }, { $form }
+announce-empty = empty
+announce-rejected = No match, swap undone
+announce-match = Matched { $count } { $forms }
+announce-points = { $points ->
+    [one] { $points } point
+   *[other] { $points } points
+}, score { $score }
+announce-moves-left = { $moves ->
+    [one] { $moves } move left
+   *[other] { $moves } moves left
+}
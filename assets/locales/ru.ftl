@@ -0,0 +1,178 @@
+game-title = Три в ряд
+
+## Главное меню
+
+menu-continue = Продолжить
+menu-campaign = Кампания
+menu-watch-replay = Смотреть последний повтор
+menu-editor = Редактор уровней
+menu-settings = Настройки
+menu-high-scores = Рекорды
+menu-quit = Выход
+menu-back = Назад
+
+mode-classic = Классика
+mode-moves = На ходы
+mode-timed = На время
+mode-versus = Вдвоём
+mode-race = Гонка
+mode-daily = Испытание дня
+mode-campaign = Кампания
+
+## Настройки
+
+on = вкл.
+off = выкл.
+settings-fullscreen = Полный экран: { $value }
+settings-vsync = VSync: { $value }
+volume-master = Общая громкость: { $percent }%
+volume-music = Музыка: { $percent }%
+volume-effects = Звуки: { $percent }%
+settings-effects = Эффекты: { $value }
+settings-theme = Тема: { $value }
+settings-animation-speed = Анимация: { $percent }%
+settings-hint-delay = Подсказка: { $seconds ->
+        [0] только по H
+       *[other] через { $seconds } с
+    }
+settings-palette = Цвета: { $value }
+palette-theme = как в теме
+palette-deuteranopia = дейтеранопия
+palette-protanopia = протанопия
+palette-tritanopia = тританопия
+settings-glyphs = Значки форм: { $value }
+settings-screen-reader = Экранный диктор: { $value }
+settings-form-cues = Звуки форм: { $value }
+settings-language = Язык: { $value }
+
+## Партия
+
+pause-title = Пауза
+pause-resume = Продолжить
+pause-restart = Заново
+pause-main-menu = Главное меню
+game-over-title = Игра окончена
+versus-winner = { $player } побеждает!
+versus-draw = Ничья!
+score = Счёт: { $score }
+daily-best = Лучший счёт дня: { $score }
+daily-streak = Серия: { $days ->
+        [one] { $days } день
+        [few] { $days } дня
+       *[many] { $days } дней
+    }
+hud-moves = Ходов: { $moves }
+hud-time = Время: { $seconds }
+hud-undo = Отмена (Ctrl+Z): { $undos }
+hud-autoplay = Автоигра (A): вкл.
+player-one = Игрок 1
+player-two = Игрок 2
+hud-player-score = { $player }: { $score }
+hud-turn = Ходит { $player }, раунд { $round } из { $rounds }
+hud-target = Цель: { $score }
+hud-daily = Испытание дня { $date }
+hud-collect = { $form }: { $collected }/{ $count }
+net-you = Вы — { $player }
+net-disconnected = Соперник отключился
+net-desync = Доски разошлись на ходу { $turn }
+replay-status = Повтор { $speed }x
+replay-paused = { replay-status } (пауза)
+replay-help =
+    Скорость: - / =
+    Пауза: пробел
+
+## Кампания
+
+campaign-title = Кампания
+campaign-unavailable = Не удалось загрузить кампанию
+campaign-loading = Кампания загружается…
+campaign-level-loading = { $number }. Загружается…
+campaign-broken = { $number }. Не удалось загрузить
+campaign-level = { $number }. { $name }: звёзд { $stars } из { $max }
+campaign-locked = { $number }. Закрыт
+campaign-stars = Уровень пройден: звёзд { $stars } из { $max }
+campaign-failed = Уровень не пройден
+
+## Редактор уровней
+
+editor-name = Название: { $name }
+editor-rename = Переименовать
+editor-new = Новый
+editor-previous = <
+editor-next = >
+editor-undo = Отменить
+editor-redo = Вернуть
+editor-less = −
+editor-more = +
+editor-width = Ширина: { $width }
+editor-height = Высота: { $height }
+editor-moves = Ходы: { $moves ->
+        [0] без ограничений
+       *[other] { $moves }
+    }
+editor-score-goal = Цель по очкам: { $score ->
+        [0] нет
+       *[other] { $score }
+    }
+editor-collect-goal = Собрать: { $count } { $form }
+editor-collect-pick = Собрать: выберите фишку
+editor-test-play = Играть
+editor-save = Сохранить
+editor-saved = Сохранено в { $path }
+editor-save-failed = Не удалось сохранить: { $error }
+editor-return = В редактор
+
+## Рекорды
+
+new-record-title = Новый рекорд!
+enter-name = Введите имя:
+high-scores-title = Рекорды
+high-scores-standard = Обычная доска
+header-rank = №
+header-name = Имя
+header-score = Счёт
+header-moves = Ходы
+header-date = Дата
+no-records = Рекордов пока нет
+
+## Озвучка
+
+form-circle = { $count ->
+    [one] круг
+    [few] круга
+   *[many] кругов
+}
+form-square = { $count ->
+    [one] квадрат
+    [few] квадрата
+   *[many] квадратов
+}
+form-triangle = { $count ->
+    [one] треугольник
+    [few] треугольника
+   *[many] треугольников
+}
+form-rhombus = { $count ->
+    [one] ромб
+    [few] ромба
+   *[many] ромбов
+}
+form-annulus = { $count ->
+    [one] кольцо
+    [few] кольца
+   *[many] колец
+}
+announce-cell = Ряд { $row }, столбец { $column }, { $form }
+announce-empty = пусто
+announce-rejected = Совпадений нет, обмен отменён
+announce-match = Собрано { $count } { $forms }
+announce-points = { $points ->
+    [one] { $points } очко
+    [few] { $points } очка
+   *[many] { $points } очков
+}, счёт { $score }
+announce-moves-left = { $moves ->
+    [one] Остался { $moves } ход
+    [few] Осталось { $moves } хода
+   *[many] Осталось { $moves } ходов
+}
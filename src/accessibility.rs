@@ -0,0 +1,346 @@
+use accesskit::{Live, Node as AccessKitNode, Role};
+use bevy::{a11y::AccessibilityNode, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState, BoardAssets, GameSession, GameState, Selection,
+    board::{Board, BoardIndex, Form},
+    locale::Locale,
+    messages::{BoardSettled, CursorMoved, MatchFound, ScoreChanged, SwapRejected},
+    net::remote_turn,
+    replay::Playback,
+    settings::Settings,
+    versus::Player,
+};
+
+/// Режимы для тех, кому трудно различать фишки по цвету или видеть доску: палитры, значки форм,
+/// клавиатурный курсор и озвучка через AccessKit.
+///
+/// Курсор ходит стрелками, Enter или пробел выбирают клетку, как клик.
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyboardCursor>()
+            .init_resource::<Announcement>()
+            .add_systems(Startup, spawn_announcer)
+            .add_systems(OnExit(AppState::InGame), hide_cursor)
+            .add_systems(
+                Update,
+                (
+                    move_cursor
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(not(resource_exists::<Playback>))
+                        .run_if(not(remote_turn)),
+                    draw_cursor.run_if(in_state(AppState::InGame)),
+                    announce.run_if(screen_reader_enabled),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Палитра форм. `Theme` оставляет цвета темы, остальные подобраны под свой тип цветовой слепоты.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Palette {
+    #[default]
+    Theme,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 4] = [
+        Palette::Theme,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+    ];
+
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            Palette::Theme => "palette-theme",
+            Palette::Deuteranopia => "palette-deuteranopia",
+            Palette::Protanopia => "palette-protanopia",
+            Palette::Tritanopia => "palette-tritanopia",
+        }
+    }
+
+    pub fn next(&self) -> Palette {
+        let idx = Self::ALL
+            .iter()
+            .position(|palette| palette == self)
+            .unwrap();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Цвет формы в этой палитре, `None` для цветов темы.
+    ///
+    /// Цвета взяты из палитры Окабе — Ито и различаются ещё и яркостью, чтобы пары не сливались.
+    pub fn color(&self, form: Form) -> Option<Color> {
+        let [r, g, b] = match self {
+            Palette::Theme => return None,
+            Palette::Deuteranopia => match form {
+                Form::Circle => [213, 94, 0],
+                Form::Square => [86, 180, 233],
+                Form::Triangle => [0, 114, 178],
+                Form::Rhombus => [240, 228, 66],
+                Form::Annulus => [204, 121, 167],
+            },
+            Palette::Protanopia => match form {
+                Form::Circle => [230, 159, 0],
+                Form::Square => [86, 180, 233],
+                Form::Triangle => [0, 84, 147],
+                Form::Rhombus => [245, 245, 245],
+                Form::Annulus => [150, 150, 150],
+            },
+            Palette::Tritanopia => match form {
+                Form::Circle => [228, 26, 28],
+                Form::Square => [0, 158, 115],
+                Form::Triangle => [245, 245, 245],
+                Form::Rhombus => [255, 143, 176],
+                Form::Annulus => [110, 110, 110],
+            },
+        };
+
+        Some(Color::srgb_u8(r, g, b))
+    }
+}
+
+/// Значок внутри фишки. У каждой формы свой, чтобы формы различались даже без цвета.
+pub fn glyph_mesh(form: Form) -> Mesh {
+    match form {
+        Form::Circle => Circle::new(0.1).into(),
+        Form::Square => Rectangle::new(0.36, 0.1).into(),
+        Form::Triangle => Triangle2d::new(
+            Vec2::new(0., -0.12),
+            Vec2::new(-0.12, 0.08),
+            Vec2::new(0.12, 0.08),
+        )
+        .into(),
+        Form::Rhombus => Rectangle::from_size(Vec2::splat(0.18)).into(),
+        Form::Annulus => Annulus::new(0.08, 0.14).into(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub palette: Palette,
+    /// Рисовать ли значки форм внутри фишек.
+    pub glyphs: bool,
+    /// Озвучивать ли курсор, ходы и счёт.
+    pub screen_reader: bool,
+    /// Свой звук на каждую форму под курсором.
+    pub form_cues: bool,
+}
+
+fn screen_reader_enabled(settings: Res<Settings>) -> bool {
+    settings.accessibility.screen_reader
+}
+
+/// Клетка под клавиатурным курсором. Пока стрелки не нажимали, курсора нет.
+#[derive(Resource, Default)]
+pub struct KeyboardCursor(pub Option<BoardIndex>);
+
+/// Последнее, что прочитал экранный диктор.
+#[derive(Resource, Default)]
+pub struct Announcement(pub String);
+
+/// Живая область AccessKit: её текст диктор читает при каждом изменении.
+#[derive(Component)]
+struct Announcer;
+
+#[derive(Component)]
+struct CursorFrame;
+
+fn spawn_announcer(mut commands: Commands) {
+    let mut node = AccessKitNode::new(Role::Status);
+    node.set_live(Live::Polite);
+
+    commands.spawn((
+        Node {
+            display: Display::None,
+            ..Default::default()
+        },
+        AccessibilityNode(node),
+        Announcer,
+    ));
+}
+
+/// Курсор ходит по доске `GameSession::keyboard_player`.
+fn move_cursor(
+    keys: Res<ButtonInput<KeyCode>>,
+    boards: Query<(&Board, &mut Selection, &Player)>,
+    session: Res<GameSession>,
+    mut cursor: ResMut<KeyboardCursor>,
+    mut cursor_moved: MessageWriter<CursorMoved>,
+) {
+    let keyboard_player = session.keyboard_player();
+    let Some((board, mut selection, _)) = boards
+        .into_iter()
+        .find(|(_, _, owner)| **owner == keyboard_player)
+    else {
+        return;
+    };
+
+    let step = [
+        (KeyCode::ArrowUp, (1, 0)),
+        (KeyCode::ArrowDown, (-1, 0)),
+        (KeyCode::ArrowLeft, (0, -1)),
+        (KeyCode::ArrowRight, (0, 1)),
+    ]
+    .into_iter()
+    .find_map(|(key, step)| keys.just_pressed(key).then_some(step));
+
+    if let Some((row_step, col_step)) = step {
+        // Первое нажатие только показывает курсор в левом нижнем углу.
+        let cell = match cursor.0 {
+            Some(cell) => (
+                cell.row_id()
+                    .saturating_add_signed(row_step)
+                    .min(board.visible_height() - 1),
+                cell.col_id()
+                    .saturating_add_signed(col_step)
+                    .min(board.width() - 1),
+            )
+                .into(),
+            None => (0, 0).into(),
+        };
+
+        cursor.0 = Some(cell);
+        cursor_moved.write(CursorMoved {
+            cell,
+            form: board[cell.row_id()][cell.col_id()]
+                .tile
+                .map(|tile| tile.form),
+        });
+    }
+
+    if keys.any_just_pressed([KeyCode::Enter, KeyCode::Space])
+        && !session.is_over()
+        && let Some(cell) = cursor.0
+    {
+        selection.select(cell);
+    }
+}
+
+fn draw_cursor(
+    mut commands: Commands,
+    cursor: Res<KeyboardCursor>,
+    session: Res<GameSession>,
+    boards: Query<(&Board, &Player)>,
+    board_assets: Res<BoardAssets>,
+    frame: Option<Single<(&mut Transform, &mut Visibility), With<CursorFrame>>>,
+) {
+    let Some((board, _)) = boards
+        .iter()
+        .find(|(_, owner)| **owner == session.keyboard_player())
+    else {
+        return;
+    };
+    let Some(cell) = cursor.0 else {
+        if let Some(mut frame) = frame {
+            *frame.1 = Visibility::Hidden;
+        }
+        return;
+    };
+    let Vec2 { x, y } = board.get_cell_coord(cell);
+
+    match frame {
+        Some(mut frame) => {
+            frame.0.translation = Vec3::new(x, y, 0.4);
+            *frame.1 = Visibility::Inherited;
+        }
+        // Рамка чуть больше клетки и лежит под фишкой, поэтому видна её кромка.
+        None => {
+            commands.spawn((
+                Mesh2d(board_assets.rectangle_mesh.clone()),
+                MeshMaterial2d(board_assets.select_area_material.clone()),
+                Transform::from_xyz(x, y, 0.4).with_scale(Vec3::splat(board.cell_size())),
+                Visibility::Inherited,
+                CursorFrame,
+                DespawnOnExit(AppState::InGame),
+            ));
+        }
+    }
+}
+
+fn hide_cursor(mut cursor: ResMut<KeyboardCursor>) {
+    cursor.0 = None;
+}
+
+/// Собирает всё, что случилось за кадр, в одну фразу, пишет её в лог и отдаёт диктору.
+fn announce(
+    session: Res<GameSession>,
+    locale: Res<Locale>,
+    mut announcement: ResMut<Announcement>,
+    mut cursor_moved: MessageReader<CursorMoved>,
+    mut rejected_swaps: MessageReader<SwapRejected>,
+    mut matches: MessageReader<MatchFound>,
+    mut score_changes: MessageReader<ScoreChanged>,
+    mut settled: MessageReader<BoardSettled>,
+    announcer: Option<Single<&mut AccessibilityNode, With<Announcer>>>,
+) {
+    let mut parts = Vec::new();
+
+    if let Some(moved) = cursor_moved.read().last() {
+        let form = match moved.form {
+            Some(form) => form_name(&locale, form, 1),
+            None => locale.text("announce-empty"),
+        };
+        parts.push(locale.format(
+            "announce-cell",
+            &[
+                ("row", (moved.cell.row_id() + 1).into()),
+                ("column", (moved.cell.col_id() + 1).into()),
+                ("form", form.into()),
+            ],
+        ));
+    }
+    if rejected_swaps.read().count() > 0 {
+        parts.push(locale.text("announce-rejected"));
+    }
+    for found in matches.read() {
+        let count = found.cells.len();
+        parts.push(locale.format(
+            "announce-match",
+            &[
+                ("count", count.into()),
+                ("forms", form_name(&locale, found.form, count).into()),
+            ],
+        ));
+    }
+    for change in score_changes.read() {
+        if change.score > change.previous {
+            parts.push(locale.format(
+                "announce-points",
+                &[
+                    ("points", (change.score - change.previous).into()),
+                    ("score", change.score.into()),
+                ],
+            ));
+        }
+    }
+    if settled.read().count() > 0
+        && let Some(moves_left) = session.moves_left
+    {
+        parts.push(locale.format("announce-moves-left", &[("moves", moves_left.into())]));
+    }
+
+    if parts.is_empty() {
+        return;
+    }
+
+    announcement.0 = parts.join(". ");
+    info!("{}", announcement.0);
+    if let Some(mut announcer) = announcer {
+        announcer.set_value(announcement.0.clone());
+    }
+}
+
+/// Название формы в числе, которое подходит к `count`.
+pub(crate) fn form_name(locale: &Locale, form: Form, count: usize) -> String {
+    locale.format(&format!("form-{}", form.name()), &[("count", count.into())])
+}
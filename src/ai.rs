@@ -0,0 +1,107 @@
+use std::str::FromStr;
+
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+    board::BoardIndex,
+    rules::{Grid, SCORE_PER_TILE},
+};
+
+/// Сколько вариантов неизвестных фишек перебирается в каждом узле случая.
+const CHANCE_SAMPLES: usize = 4;
+pub const DEFAULT_DEPTH: u32 = 2;
+
+pub type Swap = (BoardIndex, BoardIndex);
+
+/// Как выбирать обмен среди допустимых.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strategy {
+    Random,
+    /// Больше всего очков за ряды самого обмена, без учёта каскадов.
+    Greedy,
+    /// Перебор на `depth` ходов вперёд со средним по случайным падающим фишкам.
+    Expectimax {
+        depth: u32,
+    },
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Strategy::Random),
+            "greedy" => Ok(Strategy::Greedy),
+            "expectimax" => Ok(Strategy::Expectimax {
+                depth: DEFAULT_DEPTH,
+            }),
+            _ => Err(format!("Unknown strategy {s:?}")),
+        }
+    }
+}
+
+impl Strategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Random => "random",
+            Strategy::Greedy => "greedy",
+            Strategy::Expectimax { .. } => "expectimax",
+        }
+    }
+
+    pub fn choose(&self, grid: &Grid, rng: &mut impl Rng) -> Option<Swap> {
+        let swaps = grid.valid_swaps();
+
+        match self {
+            Strategy::Random => swaps.choose(rng).copied(),
+            Strategy::Greedy => swaps
+                .iter()
+                .copied()
+                .max_by_key(|swap| immediate_score(grid, *swap)),
+            Strategy::Expectimax { depth } => swaps
+                .iter()
+                .copied()
+                .map(|swap| (swap, expected_score(grid, swap, (*depth).max(1), rng)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(swap, _)| swap),
+        }
+    }
+}
+
+/// Очки за ряды, которые даёт сам обмен.
+pub fn immediate_score(grid: &Grid, (a, b): Swap) -> usize {
+    grid.runs_after_swap(a, b)
+        .iter()
+        .map(|run| SCORE_PER_TILE * run.len())
+        .sum()
+}
+
+/// Средние очки за обмен и лучшие `depth - 1` ходов после него.
+///
+/// Скрытые ряды игроку не видны, поэтому каждый раз они заменяются случайными фишками.
+fn expected_score(grid: &Grid, (a, b): Swap, depth: u32, rng: &mut impl Rng) -> f64 {
+    let mut total = 0.;
+
+    for _ in 0..CHANCE_SAMPLES {
+        let mut sample = grid.clone();
+        sample.randomize_hidden(rng);
+
+        let Some(resolution) = sample.try_swap(a, b, rng) else {
+            continue;
+        };
+        total += resolution.score as f64 + best_expected_score(&sample, depth - 1, rng);
+    }
+
+    total / CHANCE_SAMPLES as f64
+}
+
+fn best_expected_score(grid: &Grid, depth: u32, rng: &mut impl Rng) -> f64 {
+    if depth == 0 {
+        return 0.;
+    }
+
+    grid.valid_swaps()
+        .into_iter()
+        .map(|swap| expected_score(grid, swap, depth, rng))
+        .fold(0., f64::max)
+}
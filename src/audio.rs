@@ -0,0 +1,253 @@
+use bevy::{audio::Volume, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameSession, GameState, ScoreStorage,
+    board::Form,
+    daily::DailyRecords,
+    highscores::HighScores,
+    messages::{BoardSettled, CursorMoved, MatchFound, SwapRejected, SwapRequested},
+    settings::Settings,
+};
+
+/// На сколько меняется громкость одним нажатием в настройках.
+const VOLUME_STEP: f32 = 0.1;
+/// Каждая следующая волна каскада звучит чуть выше предыдущей.
+const CASCADE_PITCH_STEP: f32 = 0.08;
+const MAX_CASCADE_PITCH: f32 = 1.6;
+
+/// Музыка и звуки на события доски. Без `AudioPlugin` из Bevy, например в тестах, просто ничего не слышно.
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sounds>()
+            .add_systems(Startup, start_music)
+            .add_systems(OnEnter(GameState::GameOver), play_game_over_sound)
+            .add_systems(
+                Update,
+                (
+                    play_gameplay_sounds,
+                    play_form_cues,
+                    update_music_volume.run_if(resource_changed::<Settings>),
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Effects,
+}
+
+impl VolumeChannel {
+    pub const ALL: [VolumeChannel; 3] = [
+        VolumeChannel::Master,
+        VolumeChannel::Music,
+        VolumeChannel::Effects,
+    ];
+
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "volume-master",
+            VolumeChannel::Music => "volume-music",
+            VolumeChannel::Effects => "volume-effects",
+        }
+    }
+}
+
+/// Громкости от 0 до 1. Итоговая громкость музыки и звуков умножается на общую.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.5,
+            effects: 0.8,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn get(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            VolumeChannel::Music => self.music,
+            VolumeChannel::Effects => self.effects,
+        }
+    }
+
+    /// Прибавляет громкость на шаг, после максимума начинает с нуля.
+    pub fn step(&mut self, channel: VolumeChannel) {
+        let volume = match channel {
+            VolumeChannel::Master => &mut self.master,
+            VolumeChannel::Music => &mut self.music,
+            VolumeChannel::Effects => &mut self.effects,
+        };
+
+        let steps = (*volume / VOLUME_STEP).round() as u32 + 1;
+        *volume = if steps as f32 * VOLUME_STEP > 1. + f32::EPSILON {
+            0.
+        } else {
+            steps as f32 * VOLUME_STEP
+        };
+    }
+
+    pub fn music_volume(&self) -> Volume {
+        Volume::Linear(self.master * self.music)
+    }
+
+    pub fn effects_volume(&self) -> Volume {
+        Volume::Linear(self.master * self.effects)
+    }
+}
+
+#[derive(Resource)]
+struct Sounds {
+    music: Handle<AudioSource>,
+    swap: Handle<AudioSource>,
+    invalid_swap: Handle<AudioSource>,
+    matched: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+    lose: Handle<AudioSource>,
+    /// Звук каждой формы под курсором в порядке `Form::ALL`.
+    forms: Vec<Handle<AudioSource>>,
+}
+
+impl FromWorld for Sounds {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            music: asset_server.load("sounds/music.wav"),
+            swap: asset_server.load("sounds/swap.wav"),
+            invalid_swap: asset_server.load("sounds/invalid.wav"),
+            matched: asset_server.load("sounds/match.wav"),
+            win: asset_server.load("sounds/win.wav"),
+            lose: asset_server.load("sounds/lose.wav"),
+            forms: Form::ALL
+                .iter()
+                .map(|form| asset_server.load(format!("sounds/forms/{}.wav", form.name())))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Music;
+
+#[derive(Component)]
+pub struct SoundEffect;
+
+fn start_music(mut commands: Commands, sounds: Res<Sounds>, settings: Res<Settings>) {
+    commands.spawn((
+        AudioPlayer(sounds.music.clone()),
+        PlaybackSettings::LOOP.with_volume(settings.audio.music_volume()),
+        Music,
+    ));
+}
+
+fn update_music_volume(settings: Res<Settings>, music: Query<&mut AudioSink, With<Music>>) {
+    for mut sink in music {
+        sink.set_volume(settings.audio.music_volume());
+    }
+}
+
+fn play_effect(
+    commands: &mut Commands,
+    sound: &Handle<AudioSource>,
+    settings: &AudioSettings,
+    speed: f32,
+) {
+    commands.spawn((
+        AudioPlayer(sound.clone()),
+        PlaybackSettings::DESPAWN
+            .with_volume(settings.effects_volume())
+            .with_speed(speed),
+        SoundEffect,
+    ));
+}
+
+/// За кадр каждый звук играет не больше одного раза, иначе при быстром повторе они сливаются в шум.
+fn play_gameplay_sounds(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    mut swaps: MessageReader<SwapRequested>,
+    mut rejected_swaps: MessageReader<SwapRejected>,
+    mut matches: MessageReader<MatchFound>,
+    mut settled: MessageReader<BoardSettled>,
+    mut cascade: Local<u32>,
+) {
+    if swaps.read().count() > 0 {
+        play_effect(&mut commands, &sounds.swap, &settings.audio, 1.);
+    }
+    if rejected_swaps.read().count() > 0 {
+        play_effect(&mut commands, &sounds.invalid_swap, &settings.audio, 1.);
+    }
+    // Особых фишек пока нет, поэтому и звука для них нет.
+    if matches.read().count() > 0 {
+        let speed = (1. + CASCADE_PITCH_STEP * *cascade as f32).min(MAX_CASCADE_PITCH);
+        play_effect(&mut commands, &sounds.matched, &settings.audio, speed);
+        *cascade += 1;
+    }
+    // Волны каскада считаются заново с каждым ходом.
+    if settled.read().count() > 0 {
+        *cascade = 0;
+    }
+}
+
+/// Подсказывает форму под клавиатурным курсором, чтобы доску можно было читать на слух.
+fn play_form_cues(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    mut cursor_moved: MessageReader<CursorMoved>,
+) {
+    let Some(moved) = cursor_moved.read().last() else {
+        return;
+    };
+
+    if let Some(form) = moved.form.filter(|_| settings.accessibility.form_cues) {
+        play_effect(
+            &mut commands,
+            &sounds.forms[form as usize],
+            &settings.audio,
+            1.,
+        );
+    }
+}
+
+/// Партия считается выигранной, если счёт попал в таблицу рекордов, уровень — если выполнены его цели,
+/// а испытание дня — если побит лучший счёт дня.
+/// Поединок и гонку играют друг против друга, а не на рекорд, и они всегда заканчиваются победным звуком.
+/// Партия, где ходила автоигра, не выиграна: за игрока играл ИИ.
+fn play_game_over_sound(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    high_scores: Res<HighScores>,
+    daily_records: Res<DailyRecords>,
+) {
+    // Лучший счёт дня мог уже обновиться этой же партией, поэтому равный ему тоже победа.
+    let daily_best = session.day().map(|day| daily_records.best(day));
+    let won = session.mode.is_duel()
+        || !session.autoplayed
+            && (high_scores.qualifies(session.mode, session.level_name(), score.total())
+                || session.level_cleared()
+                || daily_best.is_some_and(|best| best.is_none_or(|best| score.total() >= best)));
+    let sound = if won { &sounds.win } else { &sounds.lose };
+
+    play_effect(&mut commands, sound, &settings.audio, 1.);
+}
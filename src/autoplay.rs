@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use crate::{
+    AppState, BoardAssets, GameSession, GameState, Selection, advance_tick, ai::Strategy,
+    board::Board, board_settled, handle_selection, net::remote_turn, replay::Playback, rules::Grid,
+    settings::Settings,
+};
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Пауза между ходами автоигры, чтобы за ней можно было следить.
+const AUTOPLAY_DELAY: Duration = Duration::from_millis(400);
+
+pub struct AutoplayPlugin;
+
+impl Plugin for AutoplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Autoplay>()
+            .init_resource::<HintTimer>()
+            .add_systems(OnExit(AppState::InGame), stop_autoplay)
+            .add_systems(
+                Update,
+                (
+                    toggle_autoplay,
+                    show_hint.run_if(board_settled),
+                    clear_hints.run_if(not(board_settled)),
+                )
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                FixedUpdate,
+                autoplay
+                    .after(advance_tick)
+                    .before(handle_selection)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(resource_exists::<Playback>))
+                    .run_if(not(remote_turn))
+                    .run_if(board_settled),
+            );
+    }
+}
+
+/// Автоигра: ИИ сам делает ходы через `Selection`, как если бы игрок кликал по фишкам.
+///
+/// Автоигра и подсказки работают, только пока доска одна: в гонке они дали бы одному игроку фору.
+#[derive(Resource)]
+pub struct Autoplay {
+    pub enabled: bool,
+    strategy: Strategy,
+    delay: Timer,
+    rng: ChaCha8Rng,
+}
+
+impl Default for Autoplay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strategy: Strategy::Greedy,
+            delay: Timer::new(AUTOPLAY_DELAY, TimerMode::Repeating),
+            rng: ChaCha8Rng::from_os_rng(),
+        }
+    }
+}
+
+/// В гонке досок две, и автоигра там не включается.
+fn toggle_autoplay(
+    keys: Res<ButtonInput<KeyCode>>,
+    session: Res<GameSession>,
+    mut autoplay: ResMut<Autoplay>,
+) {
+    if keys.just_pressed(KeyCode::KeyA) && session.mode.board_owners().len() == 1 {
+        autoplay.enabled = !autoplay.enabled;
+        autoplay.delay.reset();
+    }
+}
+
+fn stop_autoplay(mut autoplay: ResMut<Autoplay>) {
+    autoplay.enabled = false;
+}
+
+fn autoplay(
+    time: Res<Time>,
+    mut autoplay: ResMut<Autoplay>,
+    board: Single<(&Board, &mut Selection)>,
+    mut session: ResMut<GameSession>,
+) {
+    let (board, mut selection) = board.into_inner();
+    if !autoplay.enabled || session.is_over() || !autoplay.delay.tick(time.delta()).just_finished()
+    {
+        return;
+    }
+
+    let Autoplay { strategy, rng, .. } = &mut *autoplay;
+    if let Some((from, to)) = strategy.choose(&Grid::from(board), rng) {
+        selection.request_swap(from, to);
+        session.autoplayed = true;
+    }
+}
+
+#[derive(Component)]
+struct HintMarker;
+
+/// Сколько доска стоит спокойно. Когда пройдёт задержка из настроек, подсказка появится сама.
+#[derive(Resource, Default)]
+struct HintTimer(Timer);
+
+/// Подсвечивает обмен, который выбрал бы жадный ИИ, по H или после задержки из настроек.
+fn show_hint(
+    mut commands: Commands,
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut timer: ResMut<HintTimer>,
+    board: Single<&Board>,
+    board_assets: Res<BoardAssets>,
+    markers: Query<Entity, With<HintMarker>>,
+) {
+    let delay = Duration::from_secs_f32(settings.hint_delay_secs);
+    if timer.0.duration() != delay {
+        timer.0 = Timer::new(delay, TimerMode::Once);
+    }
+    let delay_passed = !delay.is_zero() && timer.0.tick(time.delta()).just_finished();
+
+    if !keys.just_pressed(KeyCode::KeyH) && !delay_passed {
+        return;
+    }
+
+    for marker in markers {
+        commands.entity(marker).despawn();
+    }
+
+    let Some((from, to)) = Strategy::Greedy.choose(&Grid::from(*board), &mut rand::rng()) else {
+        return;
+    };
+
+    for idx in [from, to] {
+        let Vec2 { x, y } = board.get_cell_coord(idx);
+
+        commands.spawn((
+            Mesh2d(board_assets.rectangle_mesh.clone()),
+            MeshMaterial2d(board_assets.select_area_material.clone()),
+            Transform::from_xyz(x, y, 0.25).with_scale(Vec3::splat(board.cell_size())),
+            HintMarker,
+            DespawnOnExit(AppState::InGame),
+        ));
+    }
+}
+
+/// Подсказка пропадает, как только на доске что-то сдвинулось, и задержка отсчитывается заново.
+fn clear_hints(
+    mut commands: Commands,
+    mut timer: ResMut<HintTimer>,
+    markers: Query<Entity, With<HintMarker>>,
+) {
+    timer.0.reset();
+    for marker in markers {
+        commands.entity(marker).despawn();
+    }
+}
@@ -0,0 +1,256 @@
+use std::{collections::BTreeMap, env, process::ExitCode, str::FromStr};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use tile_matching::{
+    ai::{DEFAULT_DEPTH, Strategy},
+    rules::{Direction, Grid, Resolution},
+};
+
+const USAGE: &str = "\
+Usage: tile-matching-sim [options]
+
+Plays games by the game rules without a window and prints statistics.
+
+Options:
+  --seed <n>          seed of the first game, next games use n+1, n+2, ... (default: random)
+  --width <n>         board width (default: 10)
+  --height <n>        visible board height (default: 10)
+  --forms <n>         number of forms, 1-5 (default: 5)
+  --strategy <name>   move selection: random, greedy, expectimax (default: greedy)
+  --depth <n>         expectimax lookahead in moves (default: 2)
+  --games <n>         number of games (default: 1000)
+  --moves <n>         moves per game (default: 30)
+  --help              print this help";
+
+struct Options {
+    seed: u64,
+    width: usize,
+    height: usize,
+    forms: usize,
+    strategy: Strategy,
+    depth: u32,
+    games: u32,
+    moves: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            seed: rand::random(),
+            width: 10,
+            height: 10,
+            forms: 5,
+            strategy: Strategy::Greedy,
+            depth: DEFAULT_DEPTH,
+            games: 1000,
+            moves: 30,
+        }
+    }
+}
+
+impl Options {
+    /// Разбирает аргументы. `Ok(None)` означает, что была запрошена справка.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self::default();
+
+        while let Some(arg) = args.next() {
+            if arg == "--help" {
+                return Ok(None);
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {arg}"))?;
+
+            match arg.as_str() {
+                "--seed" => options.seed = parse_value(&arg, &value)?,
+                "--width" => options.width = parse_value(&arg, &value)?,
+                "--height" => options.height = parse_value(&arg, &value)?,
+                "--forms" => options.forms = parse_value(&arg, &value)?,
+                "--strategy" => options.strategy = value.parse()?,
+                "--games" => options.games = parse_value(&arg, &value)?,
+                "--depth" => options.depth = parse_value(&arg, &value)?,
+                "--moves" => options.moves = parse_value(&arg, &value)?,
+                _ => return Err(format!("Unknown option {arg}")),
+            }
+        }
+
+        if options.width == 0 || options.height == 0 {
+            return Err("Board size must be positive".to_string());
+        }
+        if !(1..=5).contains(&options.forms) {
+            return Err("Number of forms must be between 1 and 5".to_string());
+        }
+        if let Strategy::Expectimax { depth } = &mut options.strategy {
+            if options.depth == 0 {
+                return Err("Expectimax depth must be positive".to_string());
+            }
+            *depth = options.depth;
+        }
+
+        Ok(Some(options))
+    }
+}
+
+fn parse_value<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {value:?} for {arg}"))
+}
+
+#[derive(Default)]
+struct Stats {
+    games: u32,
+    total_score: usize,
+    min_score: Option<usize>,
+    max_score: usize,
+    moves: u32,
+    dead_boards: u32,
+    /// Сколько ходов дали каскад такой длины.
+    cascades: BTreeMap<usize, u32>,
+    runs_of_four: u32,
+    runs_of_five: u32,
+    crossings: u32,
+}
+
+impl Stats {
+    fn record_move(&mut self, resolution: &Resolution) {
+        self.moves += 1;
+        *self.cascades.entry(resolution.cascade_len()).or_default() += 1;
+
+        for runs in &resolution.rounds {
+            for run in runs {
+                match run.len() {
+                    4 => self.runs_of_four += 1,
+                    len if len >= 5 => self.runs_of_five += 1,
+                    _ => {}
+                }
+            }
+
+            // Пересечение горизонтального и вертикального рядов — будущие фигуры L и T.
+            for horizontal in runs
+                .iter()
+                .filter(|run| run.direction == Direction::Horizontal)
+            {
+                self.crossings += runs
+                    .iter()
+                    .filter(|run| run.direction == Direction::Vertical)
+                    .filter(|vertical| {
+                        vertical
+                            .cells
+                            .iter()
+                            .any(|idx| horizontal.cells.contains(idx))
+                    })
+                    .count() as u32;
+            }
+        }
+    }
+
+    fn record_game(&mut self, score: usize, dead_board: bool) {
+        self.games += 1;
+        self.total_score += score;
+        self.min_score = Some(self.min_score.map_or(score, |min| min.min(score)));
+        self.max_score = self.max_score.max(score);
+
+        if dead_board {
+            self.dead_boards += 1;
+        }
+    }
+
+    fn print(&self, options: &Options) {
+        let percent = |count: u32, total: u32| 100. * count as f64 / total.max(1) as f64;
+        let per_move = |count: u32| count as f64 / self.moves.max(1) as f64;
+
+        println!(
+            "Games: {} (seed {}, {}x{}, {} forms, {} strategy, up to {} moves)",
+            self.games,
+            options.seed,
+            options.width,
+            options.height,
+            options.forms,
+            options.strategy.name(),
+            options.moves
+        );
+        println!(
+            "Average score: {:.1} (min {}, max {})",
+            self.total_score as f64 / self.games.max(1) as f64,
+            self.min_score.unwrap_or_default(),
+            self.max_score
+        );
+        println!(
+            "Average moves: {:.1}",
+            self.moves as f64 / self.games.max(1) as f64
+        );
+        println!(
+            "Dead boards: {} ({:.1}%)",
+            self.dead_boards,
+            percent(self.dead_boards, self.games)
+        );
+
+        println!("Cascade length:");
+        for (len, count) in &self.cascades {
+            println!("  {len}: {count} ({:.1}%)", percent(*count, self.moves));
+        }
+
+        println!("Special tile candidates per move (specials are not in the game yet):");
+        println!("  runs of 4: {:.3}", per_move(self.runs_of_four));
+        println!("  runs of 5+: {:.3}", per_move(self.runs_of_five));
+        println!("  crossing runs: {:.3}", per_move(self.crossings));
+    }
+}
+
+fn play_game(options: &Options, seed: u64, stats: &mut Stats) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    // Стратегии свой поток ГПСЧ, чтобы её выбор не сдвигал падающие фишки.
+    let mut strategy_rng = rng.clone();
+    strategy_rng.set_stream(1);
+
+    let mut grid = Grid::random(options.width, options.height, options.forms, &mut rng);
+    // Как и в игре, совпадения на стартовой доске снимаются и засчитываются.
+    let mut score = grid.resolve(&mut rng).score;
+    let mut dead_board = false;
+
+    for _ in 0..options.moves {
+        let Some((a, b)) = options.strategy.choose(&grid, &mut strategy_rng) else {
+            dead_board = true;
+            break;
+        };
+
+        let resolution = grid
+            .try_swap(a, b, &mut rng)
+            .expect("Strategy must choose a valid swap");
+
+        score += resolution.score;
+        stats.record_move(&resolution);
+    }
+
+    stats.record_game(score, dead_board);
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut stats = Stats::default();
+    for game_id in 0..options.games {
+        play_game(
+            &options,
+            options.seed.wrapping_add(game_id as u64),
+            &mut stats,
+        );
+    }
+
+    stats.print(&options);
+
+    ExitCode::SUCCESS
+}
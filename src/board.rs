@@ -1,10 +1,16 @@
-use std::ops::{Index, IndexMut};
+use std::{
+    error::Error,
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
 
-use bevy::prelude::{Entity, Resource, Vec2};
+use bevy::prelude::{Component, Entity, Vec2};
 use rand::{
     Rng,
     distr::{Distribution, StandardUniform},
 };
+use serde::{Deserialize, Serialize};
 
 const BOARD_WIDTH: usize = 10;
 const BOARD_VISIBLE_HEIGHT: usize = 10;
@@ -14,28 +20,52 @@ const CELL_BORDER_WIDTH: f32 = 2.0;
 
 pub const TILE_VELOCITY: f32 = 350.;
 
-#[derive(Resource)]
-pub struct Board(Vec<Vec<Cell>>);
+const EMPTY_SYMBOL: char = '.';
+const HOLE_SYMBOL: char = '#';
+const BLOCKER_SYMBOL: char = '@';
+const SEPARATOR_SYMBOL: char = '-';
+
+/// Доска одного игрока. Досок может быть несколько, каждая стоит на своём месте в мире.
+#[derive(Component)]
+pub struct Board {
+    rows: Vec<Vec<Cell>>,
+    /// Центр видимой части доски.
+    origin: Vec2,
+}
 
 impl Board {
+    /// Размеры у всех досок одинаковые, поэтому их можно узнать, не строя доску.
+    pub const WIDTH: usize = BOARD_WIDTH;
+    pub const HEIGHT: usize = BOARD_HEIGHT;
+    pub const VISIBLE_HEIGHT: usize = BOARD_VISIBLE_HEIGHT;
+    /// Сторона клетки вместе с рамкой.
+    pub const CELL_SIZE: f32 = BOARD_TILE_SIZE + CELL_BORDER_WIDTH;
+
     pub fn new() -> Self {
-        Self(Vec::with_capacity(BOARD_HEIGHT))
+        Self::with_origin(Vec2::ZERO)
+    }
+
+    pub fn with_origin(origin: Vec2) -> Self {
+        Self {
+            rows: Vec::with_capacity(BOARD_HEIGHT),
+            origin,
+        }
     }
 
     pub fn width(&self) -> usize {
-        BOARD_WIDTH
+        Self::WIDTH
     }
 
     pub fn height(&self) -> usize {
-        BOARD_HEIGHT
+        Self::HEIGHT
     }
 
     pub fn visible_height(&self) -> usize {
-        BOARD_VISIBLE_HEIGHT
+        Self::VISIBLE_HEIGHT
     }
 
     pub fn cell_size(&self) -> f32 {
-        self.tile_size() + CELL_BORDER_WIDTH
+        Self::CELL_SIZE
     }
 
     pub fn tile_size(&self) -> f32 {
@@ -47,10 +77,11 @@ impl Board {
     }
 
     pub fn bottom_left(&self) -> Vec2 {
-        Vec2::new(
-            -(BOARD_WIDTH as f32 * (BOARD_TILE_SIZE + CELL_BORDER_WIDTH)) / 2.,
-            -(BOARD_VISIBLE_HEIGHT as f32 * (BOARD_TILE_SIZE + CELL_BORDER_WIDTH)) / 2.,
-        )
+        self.origin
+            + Vec2::new(
+                -(BOARD_WIDTH as f32 * (BOARD_TILE_SIZE + CELL_BORDER_WIDTH)) / 2.,
+                -(BOARD_VISIBLE_HEIGHT as f32 * (BOARD_TILE_SIZE + CELL_BORDER_WIDTH)) / 2.,
+            )
     }
 
     pub fn top_right(&self) -> Vec2 {
@@ -76,11 +107,27 @@ impl Board {
             "New row length is greater than board"
         );
 
-        self.0.push(row);
+        self.rows.push(row);
     }
 
     pub fn get_row(&self, idx: usize) -> Option<&Vec<Cell>> {
-        self.0.get(idx)
+        self.rows.get(idx)
+    }
+}
+
+/// Доска в текстовой записи, сверху вниз, со строкой из `-` между скрытыми и видимыми рядами.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (row_id, row) in self.rows.iter().enumerate().rev() {
+            write_row(f, row.iter().map(Cell::layout_cell))?;
+
+            if row_id == self.visible_height() {
+                let separator = vec![SEPARATOR_SYMBOL.to_string(); row.len()];
+                writeln!(f, "{}", separator.join(" "))?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -89,7 +136,7 @@ impl<'a> IntoIterator for &'a Board {
     type IntoIter = std::slice::Iter<'a, Vec<Cell>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+        self.rows.iter()
     }
 }
 
@@ -103,13 +150,13 @@ impl Index<usize> for Board {
     type Output = Vec<Cell>;
 
     fn index(&self, index: usize) -> &Self::Output {
-        &self.0[index]
+        &self.rows[index]
     }
 }
 
 impl IndexMut<usize> for Board {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.0[index]
+        &mut self.rows[index]
     }
 }
 
@@ -117,17 +164,17 @@ impl Index<BoardIndex> for Board {
     type Output = Cell;
 
     fn index(&self, BoardIndex(row_id, col_id): BoardIndex) -> &Self::Output {
-        &self.0[row_id][col_id]
+        &self.rows[row_id][col_id]
     }
 }
 
 impl IndexMut<BoardIndex> for Board {
     fn index_mut(&mut self, BoardIndex(row_id, col_id): BoardIndex) -> &mut Self::Output {
-        &mut self.0[row_id][col_id]
+        &mut self.rows[row_id][col_id]
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct BoardIndex(usize, usize);
 
 impl BoardIndex {
@@ -146,9 +193,10 @@ impl From<(usize, usize)> for BoardIndex {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct Cell {
     pub tile: Option<Tile>,
+    pub kind: CellKind,
 }
 
 impl Cell {
@@ -159,6 +207,34 @@ impl Cell {
     pub fn tile_size(&self) -> f32 {
         BOARD_TILE_SIZE
     }
+
+    fn layout_cell(&self) -> LayoutCell {
+        match (self.tile, self.kind) {
+            (Some(tile), _) => LayoutCell::Tile(tile.form),
+            (None, CellKind::Open) => LayoutCell::Empty,
+            (None, CellKind::Hole) => LayoutCell::Hole,
+            (None, CellKind::Blocker) => LayoutCell::Blocker,
+        }
+    }
+}
+
+/// Что уровень поставил в клетку навсегда.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum CellKind {
+    /// Обычная клетка, в ней лежат и через неё падают фишки.
+    #[default]
+    Open,
+    /// Клетки нет: фишки в ней не появляются и пролетают её насквозь.
+    Hole,
+    /// Неподвижная преграда. Её нельзя обменять, в ряды она не входит, фишки пролетают её насквозь.
+    Blocker,
+}
+
+impl CellKind {
+    /// Может ли в клетке лежать фишка.
+    pub fn is_open(&self) -> bool {
+        *self == CellKind::Open
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -166,9 +242,11 @@ pub struct Tile {
     pub form: Form,
     pub entity: Entity,
     pub select_area_entity: Entity,
+    /// Дочерняя сущность с мешем формы, её перекрашивает смена темы.
+    pub visual_entity: Entity,
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Form {
     Circle,
     Square,
@@ -177,6 +255,41 @@ pub enum Form {
     Annulus,
 }
 
+impl Form {
+    pub const ALL: [Form; 5] = [
+        Form::Circle,
+        Form::Square,
+        Form::Triangle,
+        Form::Rhombus,
+        Form::Annulus,
+    ];
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Form::Circle => 'C',
+            Form::Square => 'S',
+            Form::Triangle => 'T',
+            Form::Rhombus => 'R',
+            Form::Annulus => 'A',
+        }
+    }
+
+    /// Название для озвучки и имён файлов.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Form::Circle => "circle",
+            Form::Square => "square",
+            Form::Triangle => "triangle",
+            Form::Rhombus => "rhombus",
+            Form::Annulus => "annulus",
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|form| form.symbol() == symbol)
+    }
+}
+
 impl Distribution<Form> for StandardUniform {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Form {
         match rng.random_range(0..5) {
@@ -188,3 +301,262 @@ impl Distribution<Form> for StandardUniform {
         }
     }
 }
+
+/// Клетка в текстовой записи доски.
+///
+/// Дыры и преграды становятся неподвижными клетками доски, а особая фишка играет как обычная фишка своей формы.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LayoutCell {
+    Tile(Form),
+    Empty,
+    /// Клетка, которой нет на доске.
+    Hole,
+    /// Клетка, занятая неподвижной преградой.
+    Blocker,
+    /// Особая фишка, в записи строчная буква формы.
+    Special(Form),
+}
+
+impl LayoutCell {
+    pub fn symbol(&self) -> char {
+        match self {
+            LayoutCell::Tile(form) => form.symbol(),
+            LayoutCell::Empty => EMPTY_SYMBOL,
+            LayoutCell::Hole => HOLE_SYMBOL,
+            LayoutCell::Blocker => BLOCKER_SYMBOL,
+            LayoutCell::Special(form) => form.symbol().to_ascii_lowercase(),
+        }
+    }
+
+    fn from_symbol(symbol: char) -> Option<Self> {
+        match symbol {
+            EMPTY_SYMBOL => Some(LayoutCell::Empty),
+            HOLE_SYMBOL => Some(LayoutCell::Hole),
+            BLOCKER_SYMBOL => Some(LayoutCell::Blocker),
+            _ if symbol.is_ascii_lowercase() => {
+                Form::from_symbol(symbol.to_ascii_uppercase()).map(LayoutCell::Special)
+            }
+            _ => Form::from_symbol(symbol).map(LayoutCell::Tile),
+        }
+    }
+
+    /// Форма фишки в клетке, особая фишка тоже фишка.
+    pub fn form(&self) -> Option<Form> {
+        match self {
+            LayoutCell::Tile(form) | LayoutCell::Special(form) => Some(*form),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> CellKind {
+        match self {
+            LayoutCell::Hole => CellKind::Hole,
+            LayoutCell::Blocker => CellKind::Blocker,
+            _ => CellKind::Open,
+        }
+    }
+}
+
+/// Содержимое доски в компактной текстовой записи, например для тестов и отладочного вывода.
+///
+/// По строке на ряд, верхний ряд первый. Формы пишутся буквами `C S T R A`, `.` — пустая клетка,
+/// `#` — дыра, `@` — преграда, строчная буква — особая фишка. Пробелы между клетками необязательны, строки из `-` и пустые строки пропускаются.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct BoardLayout(Vec<Vec<LayoutCell>>);
+
+impl BoardLayout {
+    /// Доска из одних пустых клеток.
+    pub fn empty(width: usize, height: usize) -> Self {
+        Self(vec![vec![LayoutCell::Empty; width]; height])
+    }
+
+    pub fn width(&self) -> usize {
+        self.0.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn get(&self, idx: impl Into<BoardIndex>) -> LayoutCell {
+        let BoardIndex(row_id, col_id) = idx.into();
+
+        self.0[row_id][col_id]
+    }
+
+    pub fn set(&mut self, idx: impl Into<BoardIndex>, cell: LayoutCell) {
+        let BoardIndex(row_id, col_id) = idx.into();
+
+        self.0[row_id][col_id] = cell;
+    }
+
+    /// Клетка по индексу доски; всё, что за пределами записи, обычные клетки.
+    pub fn kind_at(&self, idx: impl Into<BoardIndex>) -> CellKind {
+        let BoardIndex(row_id, col_id) = idx.into();
+
+        self.0
+            .get(row_id)
+            .and_then(|row| row.get(col_id))
+            .map_or(CellKind::Open, LayoutCell::kind)
+    }
+
+    /// Форма фишки в клетке или `None`, если клетки нет в записи или фишки в ней нет.
+    pub fn form_at(&self, idx: impl Into<BoardIndex>) -> Option<Form> {
+        let BoardIndex(row_id, col_id) = idx.into();
+
+        self.0.get(row_id)?.get(col_id)?.form()
+    }
+
+    /// Меняет размер, не трогая нижний левый угол: лишние клетки обрезаются, новые пустые.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.0.resize_with(height, Vec::new);
+        for row in &mut self.0 {
+            row.resize(width, LayoutCell::Empty);
+        }
+    }
+
+    /// Формы по клеткам, нижний ряд первый, если на доске нет пустых клеток и дыр.
+    pub fn forms(&self) -> Option<Vec<Vec<Form>>> {
+        self.0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        LayoutCell::Tile(form) => Some(*form),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl From<&Board> for BoardLayout {
+    fn from(board: &Board) -> Self {
+        Self(
+            board
+                .into_iter()
+                .map(|row| row.iter().map(Cell::layout_cell).collect())
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for BoardLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.0.iter().rev() {
+            write_row(f, row.iter().copied())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_row(f: &mut fmt::Formatter<'_>, cells: impl Iterator<Item = LayoutCell>) -> fmt::Result {
+    let symbols = cells
+        .map(|cell| cell.symbol().to_string())
+        .collect::<Vec<_>>();
+
+    writeln!(f, "{}", symbols.join(" "))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseLayoutError {
+    NoRows,
+    UnknownSymbol {
+        line: usize,
+        symbol: char,
+    },
+    RowLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseLayoutError::NoRows => write!(f, "Board has no rows"),
+            ParseLayoutError::UnknownSymbol { line, symbol } => {
+                write!(f, "Unknown symbol {symbol:?} on line {line}")
+            }
+            ParseLayoutError::RowLength {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row on line {line} has {found} cells, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl Error for ParseLayoutError {}
+
+impl FromStr for BoardLayout {
+    type Err = ParseLayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows: Vec<Vec<LayoutCell>> = Vec::new();
+
+        for (line_id, line) in s.lines().enumerate() {
+            let line_number = line_id + 1;
+            let symbols = line
+                .chars()
+                .filter(|symbol| !symbol.is_whitespace())
+                .collect::<Vec<_>>();
+
+            if symbols.iter().all(|symbol| *symbol == SEPARATOR_SYMBOL) {
+                continue;
+            }
+
+            let row = symbols
+                .into_iter()
+                .map(|symbol| {
+                    LayoutCell::from_symbol(symbol).ok_or(ParseLayoutError::UnknownSymbol {
+                        line: line_number,
+                        symbol,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(first) = rows.first()
+                && first.len() != row.len()
+            {
+                return Err(ParseLayoutError::RowLength {
+                    line: line_number,
+                    expected: first.len(),
+                    found: row.len(),
+                });
+            }
+
+            rows.push(row);
+        }
+
+        if rows.is_empty() {
+            return Err(ParseLayoutError::NoRows);
+        }
+
+        // В записи верхний ряд первый, а на доске нулевой ряд нижний.
+        rows.reverse();
+
+        Ok(Self(rows))
+    }
+}
+
+impl From<BoardLayout> for String {
+    fn from(layout: BoardLayout) -> Self {
+        layout.to_string()
+    }
+}
+
+impl TryFrom<String> for BoardLayout {
+    type Error = ParseLayoutError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
@@ -0,0 +1,338 @@
+use std::{collections::BTreeMap, io};
+
+use bevy::{
+    asset::{AssetLoadFailedEvent, AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState, GameMode, GameSession, GameState, ScoreStorage,
+    level::{Level, LevelLoader},
+    locale::Locale,
+    menu::{
+        MenuAction, MenuState, NORMAL_BUTTON, label, localized_label, menu_button, screen_root,
+        title,
+    },
+    replay::Playback,
+    settings::Settings,
+    storage,
+};
+
+const CAMPAIGN_MANIFEST: &str = "levels/main.campaign.ron";
+const LOCKED_TEXT_COLOR: Color = Color::srgb(0.45, 0.45, 0.5);
+
+/// Кампания из `assets/levels`: главы с уровнями, звёзды за уровни и открытие следующих.
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_asset::<Campaign>()
+            .init_asset_loader::<CampaignLoader>()
+            .init_resource::<CampaignProgress>()
+            .add_systems(Startup, load_campaign)
+            .add_systems(OnEnter(MenuState::Campaign), spawn_campaign_map)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                record_campaign.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    start_campaign_level,
+                    (despawn_campaign_map, spawn_campaign_map).chain().run_if(
+                        on_message::<AssetEvent<Campaign>>
+                            .or(on_message::<AssetEvent<Level>>)
+                            .or(on_message::<AssetLoadFailedEvent<Campaign>>)
+                            .or(on_message::<AssetLoadFailedEvent<Level>>),
+                    ),
+                )
+                    .run_if(in_state(MenuState::Campaign)),
+            );
+    }
+}
+
+/// Описание кампании: главы по порядку и пути к файлам их уровней от каталога `assets`.
+///
+/// Уровни открываются по порядку через все главы: следующий — когда пройден предыдущий.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct Campaign {
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Chapter {
+    pub name: String,
+    pub levels: Vec<String>,
+    #[serde(skip)]
+    pub handles: Vec<Handle<Level>>,
+}
+
+#[derive(Default)]
+struct CampaignLoader;
+
+impl AssetLoader for CampaignLoader {
+    type Asset = Campaign;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Campaign, Self::Error> {
+        let mut campaign: Campaign = storage::read_ron_asset(reader).await?;
+        for chapter in &mut campaign.chapters {
+            chapter.handles = chapter
+                .levels
+                .iter()
+                .map(|path| load_context.load(path.clone()))
+                .collect();
+        }
+
+        Ok(campaign)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct CampaignHandle(Handle<Campaign>);
+
+fn load_campaign(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CampaignHandle(asset_server.load(CAMPAIGN_MANIFEST)));
+}
+
+/// Лучшие звёзды пройденных уровней по их путям в манифесте.
+///
+/// Хранится в файле сохранения вместе с отложенной партией, пишет его `SaveGamePlugin`.
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct CampaignProgress {
+    stars: BTreeMap<String, u8>,
+}
+
+impl CampaignProgress {
+    /// Звёзды уровня, ноль — уровень ещё не пройден.
+    pub fn stars(&self, level: &str) -> u8 {
+        self.stars.get(level).copied().unwrap_or(0)
+    }
+
+    /// Запоминает звёзды, если их больше прежних. Возвращает, запомнил ли.
+    pub fn record(&mut self, level: &str, stars: u8) -> bool {
+        let improved = stars > self.stars(level);
+        if improved {
+            self.stars.insert(level.to_string(), stars);
+        }
+
+        improved
+    }
+
+    /// Сколько уровней по порядку открыто: первый всегда, каждый следующий — когда пройден предыдущий.
+    pub fn unlocked<'a>(&self, levels: impl IntoIterator<Item = &'a str>) -> usize {
+        1 + levels
+            .into_iter()
+            .take_while(|level| self.stars(level) > 0)
+            .count()
+    }
+}
+
+/// Кнопка открытого уровня на карте кампании и путь уровня в манифесте.
+#[derive(Component)]
+struct LevelButton(Handle<Level>, String);
+
+#[derive(Component)]
+struct CampaignMap;
+
+fn despawn_campaign_map(mut commands: Commands, maps: Query<Entity, With<CampaignMap>>) {
+    for map in maps {
+        commands.entity(map).despawn();
+    }
+}
+
+/// Главы столбцами, в каждой уровни со звёздами. Закрытые уровни показываются, но не нажимаются.
+///
+/// Карта строится заново, когда догружаются или меняются манифест и уровни. Уровень, который не
+/// загрузился, помечается на карте и пропускается: следующий открывается, как если бы его не было.
+fn spawn_campaign_map(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    handle: Res<CampaignHandle>,
+    campaigns: Res<Assets<Campaign>>,
+    levels: Res<Assets<Level>>,
+    progress: Res<CampaignProgress>,
+    locale: Res<Locale>,
+) {
+    let campaign = campaigns.get(&handle.0);
+    let is_broken = |handle: &Handle<Level>| asset_server.load_state(handle).is_failed();
+
+    commands
+        .spawn((
+            screen_root(Color::NONE),
+            CampaignMap,
+            DespawnOnExit(MenuState::Campaign),
+        ))
+        .with_children(|parent| {
+            parent.spawn(title("campaign-title"));
+
+            let Some(campaign) = campaign else {
+                parent.spawn(localized_label(
+                    if asset_server.load_state(&handle.0).is_failed() {
+                        "campaign-unavailable"
+                    } else {
+                        "campaign-loading"
+                    },
+                ));
+                parent.spawn(menu_button("menu-back", MenuAction::Back));
+                return;
+            };
+
+            let unlocked = progress.unlocked(
+                campaign
+                    .chapters
+                    .iter()
+                    .flat_map(|chapter| chapter.levels.iter().zip(&chapter.handles))
+                    .filter(|(_, handle)| !is_broken(handle))
+                    .map(|(path, _)| path.as_str()),
+            );
+            let mut number = 0;
+            let mut playable = 0;
+
+            parent
+                .spawn(Node {
+                    column_gap: px(24),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for chapter in &campaign.chapters {
+                        parent
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                row_gap: px(12),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(label(chapter.name.clone()));
+
+                                for (path, handle) in chapter.levels.iter().zip(&chapter.handles) {
+                                    number += 1;
+                                    let Some(level) = levels.get(handle) else {
+                                        let id = if is_broken(handle) {
+                                            "campaign-broken"
+                                        } else {
+                                            playable += 1;
+                                            "campaign-level-loading"
+                                        };
+                                        parent.spawn(level_node()).with_children(|parent| {
+                                            parent.spawn((
+                                                label(
+                                                    locale.format(id, &[("number", number.into())]),
+                                                ),
+                                                TextColor(LOCKED_TEXT_COLOR),
+                                            ));
+                                        });
+                                        continue;
+                                    };
+                                    playable += 1;
+                                    let is_unlocked = playable <= unlocked;
+                                    let text = if is_unlocked {
+                                        locale.format(
+                                            "campaign-level",
+                                            &[
+                                                ("number", number.into()),
+                                                ("name", level.name.as_str().into()),
+                                                ("stars", progress.stars(path).into()),
+                                                ("max", level.max_stars().into()),
+                                            ],
+                                        )
+                                    } else {
+                                        locale
+                                            .format("campaign-locked", &[("number", number.into())])
+                                    };
+
+                                    let mut button = parent.spawn(level_node());
+                                    if is_unlocked {
+                                        button.insert((
+                                            Button,
+                                            LevelButton(handle.clone(), path.clone()),
+                                        ));
+                                    }
+                                    button.with_children(|parent| {
+                                        let mut text = parent.spawn(label(text));
+                                        if !is_unlocked {
+                                            text.insert(TextColor(LOCKED_TEXT_COLOR));
+                                        }
+                                    });
+                                }
+                            });
+                    }
+                });
+
+            parent.spawn(menu_button("menu-back", MenuAction::Back));
+        });
+}
+
+fn level_node() -> impl Bundle {
+    (
+        Node {
+            width: px(320),
+            height: px(54),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        BackgroundColor(NORMAL_BUTTON),
+    )
+}
+
+fn start_campaign_level(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &LevelButton), Changed<Interaction>>,
+    levels: Res<Assets<Level>>,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, LevelButton(handle, path)) in buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(level) = levels.get(handle) else {
+            continue;
+        };
+
+        commands.insert_resource(
+            GameSession::new(GameMode::Campaign, settings.animation_speed)
+                .with_level(level.clone())
+                .with_level_path(path.clone()),
+        );
+        next_state.set(AppState::InGame);
+    }
+}
+
+/// Пройденный уровень кампании приносит звёзды, а с ними открывается следующий.
+/// Уровень, который прошла автоигра, игрок не проходил.
+fn record_campaign(
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    mut progress: ResMut<CampaignProgress>,
+) {
+    if session.mode != GameMode::Campaign || !session.level_cleared() || session.autoplayed {
+        return;
+    }
+    let (Some(level), Some(path)) = (session.level(), session.level_path()) else {
+        return;
+    };
+
+    // Файл сохранения переписывается, только если звёзд стало больше.
+    if progress
+        .bypass_change_detection()
+        .record(path, level.stars(score.total()))
+    {
+        progress.set_changed();
+    }
+}
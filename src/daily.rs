@@ -0,0 +1,184 @@
+use std::{collections::BTreeMap, env, io, path::PathBuf};
+
+use bevy::prelude::*;
+use chrono::{Datelike, Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState, GameMode, GameSession, GameState, ScoreStorage,
+    replay::Playback,
+    settings::Settings,
+    storage::{self, StorageRoot},
+};
+
+const DAILY_FILE: &str = "daily.ron";
+const DAILY_VERSION: u32 = 1;
+/// Ходов на испытание дня, у всех одинаково.
+pub(crate) const DAILY_MOVES: u32 = 20;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        let root = app
+            .init_resource::<StorageRoot>()
+            .world()
+            .resource::<StorageRoot>();
+        let records = DailyRecords::load(root);
+
+        app.insert_resource(records)
+            .add_systems(Startup, play_daily_from_args)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                record_daily.run_if(not(resource_exists::<Playback>)),
+            );
+    }
+}
+
+/// Зерно испытания дня.
+///
+/// По нему все в этот день получают одну доску и одни пополнения, а старый день можно сыграть заново.
+/// Поэтому зерно считается явной формулой, а не хешем стандартной библиотеки, который может измениться
+/// между версиями Rust: номер дня от начала эры перемешивается шагом SplitMix64. Менять формулу нельзя.
+pub fn daily_seed(day: NaiveDate) -> u64 {
+    let mut z = (day.num_days_from_ce() as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    z ^ (z >> 31)
+}
+
+/// Сегодняшний день по местному времени: испытание сменяется в полночь у игрока.
+pub(crate) fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Лучший счёт за каждый сыгранный день испытания.
+#[derive(Resource, Serialize, Deserialize, Debug)]
+pub struct DailyRecords {
+    version: u32,
+    best: BTreeMap<NaiveDate, usize>,
+}
+
+impl Default for DailyRecords {
+    fn default() -> Self {
+        Self {
+            version: DAILY_VERSION,
+            best: BTreeMap::new(),
+        }
+    }
+}
+
+impl DailyRecords {
+    fn path(root: &StorageRoot) -> Option<PathBuf> {
+        root.data_path(DAILY_FILE)
+    }
+
+    fn load(root: &StorageRoot) -> Self {
+        let Some(path) = Self::path(root) else {
+            return Self::default();
+        };
+
+        match storage::read_ron::<Self>(&path) {
+            Ok(records) if records.version == DAILY_VERSION => records,
+            Ok(records) => {
+                warn!(
+                    "Unsupported daily records version {} (expected {DAILY_VERSION})",
+                    records.version
+                );
+                storage::back_up(&path);
+                Self::default()
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                warn!(
+                    "Failed to read daily records from {}: {err}",
+                    path.display()
+                );
+                storage::back_up(&path);
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self, root: &StorageRoot) -> io::Result<()> {
+        let path = Self::path(root).ok_or_else(|| io::Error::other("No data directory"))?;
+
+        storage::write_ron(&path, self)
+    }
+
+    pub fn best(&self, day: NaiveDate) -> Option<usize> {
+        self.best.get(&day).copied()
+    }
+
+    /// Запоминает счёт дня, если день ещё не сыгран или счёт лучше прежнего. Возвращает, запомнил ли.
+    pub fn record(&mut self, day: NaiveDate, score: usize) -> bool {
+        let improved = self.best(day).is_none_or(|best| score > best);
+        if improved {
+            self.best.insert(day, score);
+        }
+
+        improved
+    }
+
+    /// Сколько дней подряд сыграно испытание к дню `today`. Несыгранный ещё сегодняшний день серию не рвёт.
+    pub fn streak(&self, today: NaiveDate) -> u32 {
+        let mut day = if self.best.contains_key(&today) {
+            today
+        } else {
+            today - Days::new(1)
+        };
+        let mut streak = 0;
+
+        while self.best.contains_key(&day) {
+            streak += 1;
+            day = day - Days::new(1);
+        }
+
+        streak
+    }
+}
+
+/// Записывает только испытание, начатое в свой день: прошлые дни можно переиграть, но серию задним
+/// числом не добрать. Начатое до полуночи испытание засчитывается и после неё.
+fn record_daily(
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    mut records: ResMut<DailyRecords>,
+    root: Res<StorageRoot>,
+) {
+    // За партию с автоигрой играл ИИ, в рекорды дня она не идёт.
+    if session.autoplayed {
+        return;
+    }
+    let Some(day) = session.day().filter(|day| *day == session.started_on()) else {
+        return;
+    };
+
+    if records.record(day, score.total())
+        && let Err(err) = records.save(&root)
+    {
+        warn!("Failed to save daily records: {err}");
+    }
+}
+
+/// `--daily <ГГГГ-ММ-ДД>` запускает испытание прошедшего дня с той же доской, что была в тот день.
+fn play_daily_from_args(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(arg) = env::args().skip_while(|arg| arg != "--daily").nth(1) else {
+        return;
+    };
+
+    match arg.parse::<NaiveDate>() {
+        Ok(day) => {
+            commands.insert_resource(
+                GameSession::new(GameMode::Daily, settings.animation_speed).with_day(day),
+            );
+            next_state.set(AppState::InGame);
+        }
+        Err(err) => error!("Invalid daily challenge date {arg}: {err}"),
+    }
+}
@@ -0,0 +1,851 @@
+use std::{fs, io, mem, path::PathBuf};
+
+use bevy::{
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+
+use crate::{
+    AppState, BLOCKER_COLOR, GameMode, GameSession,
+    accessibility::form_name,
+    board::{Board, BoardIndex, BoardLayout, Form, LayoutCell},
+    level::{LEVEL_EXTENSION, Level, LevelGoal},
+    locale::Locale,
+    menu::{NORMAL_BUTTON, label, localized_label},
+    settings::Settings,
+    storage,
+    theme::ActiveTheme,
+};
+
+/// Уровни редактора лежат в каталоге данных, каждый в файле по своему имени.
+const LEVELS_DIR: &str = "levels";
+const NEW_LEVEL_NAME: &str = "Custom";
+const MAX_NAME_LEN: usize = 24;
+const MIN_SIDE: usize = 3;
+/// Столько правок можно отменить.
+const HISTORY_LIMIT: usize = 100;
+const MOVES_STEP: u32 = 5;
+const SCORE_STEP: usize = 100;
+const COLLECT_STEP: usize = 5;
+const CELL_SIZE: f32 = 44.;
+const BRUSH_COLOR: Color = Color::srgb(0.9, 0.9, 0.95);
+
+/// Кисти палитры: пустая клетка, дыра, преграда, затем фишки и особые фишки каждой формы.
+const PALETTE: [LayoutCell; 13] = [
+    LayoutCell::Empty,
+    LayoutCell::Hole,
+    LayoutCell::Blocker,
+    LayoutCell::Tile(Form::Circle),
+    LayoutCell::Tile(Form::Square),
+    LayoutCell::Tile(Form::Triangle),
+    LayoutCell::Tile(Form::Rhombus),
+    LayoutCell::Tile(Form::Annulus),
+    LayoutCell::Special(Form::Circle),
+    LayoutCell::Special(Form::Square),
+    LayoutCell::Special(Form::Triangle),
+    LayoutCell::Special(Form::Rhombus),
+    LayoutCell::Special(Form::Annulus),
+];
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Editor), spawn_editor)
+            .add_systems(OnExit(AppState::Editor), finish_naming)
+            .add_systems(
+                Update,
+                (
+                    editor_shortcuts.run_if(not(resource_exists::<NameDraft>)),
+                    type_level_name.run_if(resource_exists::<NameDraft>),
+                    handle_editor_actions,
+                    paint_cells,
+                    rebuild_grid.run_if(resource_changed::<LevelEditor>),
+                    display_editor,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Editor)),
+            );
+    }
+}
+
+/// Уровень в редакторе, выбранная кисть и история правок.
+///
+/// Ресурс живёт, пока дизайнер не вышел из редактора в главное меню, поэтому после пробной игры
+/// редактор открывается с тем же уровнем и той же историей.
+#[derive(Resource)]
+pub struct LevelEditor {
+    level: Level,
+    brush: LayoutCell,
+    undo: Vec<Level>,
+    redo: Vec<Level>,
+    /// Мазок кисти, пока зажата кнопка мыши: `Some(true)`, когда он уже попал в историю.
+    stroke: Option<bool>,
+    /// Итог последнего сохранения для строки состояния.
+    status: Option<String>,
+}
+
+/// Открывает уровень, сохранённый редактором последним, или пустую доску.
+impl Default for LevelEditor {
+    fn default() -> Self {
+        let level = saved_levels()
+            .into_iter()
+            .max_by_key(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+            .and_then(|path| match Level::load(&path) {
+                Ok(level) => Some(level),
+                Err(err) => {
+                    warn!("Failed to read level from {}: {err}", path.display());
+                    None
+                }
+            });
+
+        Self::new(level.unwrap_or_else(blank_level))
+    }
+}
+
+impl LevelEditor {
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            brush: LayoutCell::Tile(Form::Circle),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            stroke: None,
+            status: None,
+        }
+    }
+
+    /// Файл уровня называется по его имени, так что переименованный уровень сохраняется в новый файл.
+    pub fn path(&self) -> Option<PathBuf> {
+        level_path(&self.level.name)
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn brush(&self) -> LayoutCell {
+        self.brush
+    }
+
+    pub fn set_brush(&mut self, brush: LayoutCell) {
+        self.brush = brush;
+    }
+
+    /// Ставит в клетку выбранную кисть. Все клетки одного мазка отменяются разом.
+    pub fn paint(&mut self, idx: BoardIndex) {
+        let brush = self.brush;
+
+        if self.stroke == Some(true) {
+            self.level.layout.set(idx, brush);
+        } else if self.edit(|level| level.layout.set(idx, brush)) && self.stroke.is_some() {
+            self.stroke = Some(true);
+        }
+    }
+
+    pub fn begin_stroke(&mut self) {
+        self.stroke = Some(false);
+    }
+
+    pub fn end_stroke(&mut self) {
+        self.stroke = None;
+    }
+
+    /// Переименовывает уровень. Пустое имя не меняет ничего.
+    pub fn set_name(&mut self, name: &str) {
+        let name = name.trim();
+        if !name.is_empty() {
+            self.edit(|level| level.name = name.to_string());
+        }
+    }
+
+    /// Заменяет уровень в редакторе другим, эту замену тоже можно отменить.
+    pub fn open(&mut self, level: Level) {
+        self.edit(|current| *current = level);
+    }
+
+    /// Меняет размер доски в пределах видимой части игровой доски.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let width = width.clamp(MIN_SIDE, Board::WIDTH);
+        let height = height.clamp(MIN_SIDE, Board::VISIBLE_HEIGHT);
+
+        self.edit(|level| level.layout.resize(width, height));
+    }
+
+    pub fn set_moves(&mut self, moves: Option<u32>) {
+        self.edit(|level| level.moves = moves.filter(|moves| *moves > 0));
+    }
+
+    pub fn score_goal(&self) -> Option<usize> {
+        self.level.goals.iter().find_map(|goal| match goal {
+            LevelGoal::Score(score) => Some(*score),
+            _ => None,
+        })
+    }
+
+    /// Задаёт цель по очкам, ноль убирает цель.
+    pub fn set_score_goal(&mut self, score: usize) {
+        self.set_goal(
+            |goal| matches!(goal, LevelGoal::Score(_)),
+            (score > 0).then_some(LevelGoal::Score(score)),
+        );
+    }
+
+    pub fn collect_goal(&self, form: Form) -> usize {
+        self.level
+            .goals
+            .iter()
+            .find_map(|goal| match goal {
+                LevelGoal::Collect {
+                    form: goal_form,
+                    count,
+                } if *goal_form == form => Some(*count),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    /// Задаёт, сколько фишек формы надо собрать, ноль убирает цель.
+    pub fn set_collect_goal(&mut self, form: Form, count: usize) {
+        self.set_goal(
+            |goal| matches!(goal, LevelGoal::Collect { form: goal_form, .. } if *goal_form == form),
+            (count > 0).then_some(LevelGoal::Collect { form, count }),
+        );
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self) {
+        self.stroke = self.stroke.map(|_| false);
+        if let Some(level) = self.undo.pop() {
+            self.redo.push(mem::replace(&mut self.level, level));
+        }
+    }
+
+    pub fn redo(&mut self) {
+        self.stroke = self.stroke.map(|_| false);
+        if let Some(level) = self.redo.pop() {
+            self.undo.push(mem::replace(&mut self.level, level));
+        }
+    }
+
+    /// Партия на уровне из редактора, чтобы сразу его опробовать.
+    pub fn test_session(&self, animation_speed: f32) -> GameSession {
+        GameSession::new(GameMode::Classic, animation_speed).with_level(self.level.clone())
+    }
+
+    fn set_goal(&mut self, is_replaced: impl Fn(&LevelGoal) -> bool, goal: Option<LevelGoal>) {
+        self.edit(|level| {
+            match level.goals.iter().position(&is_replaced) {
+                Some(id) => match goal {
+                    Some(goal) => level.goals[id] = goal,
+                    None => {
+                        level.goals.remove(id);
+                    }
+                },
+                None => level.goals.extend(goal),
+            };
+        });
+    }
+
+    /// Правка, которую можно отменить. Правка без изменений в историю не попадает.
+    fn edit(&mut self, change: impl FnOnce(&mut Level)) -> bool {
+        let mut level = self.level.clone();
+        change(&mut level);
+
+        if level == self.level {
+            return false;
+        }
+
+        self.undo.push(mem::replace(&mut self.level, level));
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        true
+    }
+
+    /// Открывает соседний по имени файла сохранённый уровень.
+    fn open_next(&mut self, step: isize) {
+        let levels = saved_levels();
+        if levels.is_empty() {
+            return;
+        }
+
+        let current = self.path();
+        let id = match levels
+            .iter()
+            .position(|path| Some(path) == current.as_ref())
+        {
+            Some(id) => (id as isize + step).rem_euclid(levels.len() as isize) as usize,
+            None if step > 0 => 0,
+            None => levels.len() - 1,
+        };
+
+        match Level::load(&levels[id]) {
+            Ok(level) => self.open(level),
+            Err(err) => warn!("Failed to read level from {}: {err}", levels[id].display()),
+        }
+    }
+
+    fn save(&mut self, locale: &Locale) {
+        let result = match self.path() {
+            Some(path) => self.level.save(&path).map(|()| path),
+            None => Err(io::Error::other("No data directory")),
+        };
+
+        self.status = Some(match result {
+            Ok(path) => locale.format(
+                "editor-saved",
+                &[("path", path.display().to_string().into())],
+            ),
+            Err(err) => {
+                warn!("Failed to save level: {err}");
+                locale.format("editor-save-failed", &[("error", err.to_string().into())])
+            }
+        });
+    }
+}
+
+/// Пустая доска размером с видимую часть игровой под именем, которого ещё нет среди сохранённых.
+fn blank_level() -> Level {
+    let taken = saved_levels();
+    let name = (1..)
+        .map(|number| format!("{NEW_LEVEL_NAME} {number}"))
+        .find(|name| level_path(name).is_none_or(|path| !taken.contains(&path)))
+        .unwrap_or_else(|| NEW_LEVEL_NAME.to_string());
+
+    Level {
+        name,
+        layout: BoardLayout::empty(Board::WIDTH, Board::VISIBLE_HEIGHT),
+        moves: None,
+        goals: Vec::new(),
+        star_scores: Vec::new(),
+    }
+}
+
+fn level_path(name: &str) -> Option<PathBuf> {
+    storage::data_path(&format!(
+        "{LEVELS_DIR}/{}.{LEVEL_EXTENSION}",
+        file_stem(name)
+    ))
+}
+
+/// Имя файла уровня: слова имени строчными буквами через дефис.
+fn file_stem(name: &str) -> String {
+    let stem = name
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if stem.is_empty() {
+        NEW_LEVEL_NAME.to_lowercase()
+    } else {
+        stem
+    }
+}
+
+/// Файлы уровней в каталоге редактора по алфавиту.
+fn saved_levels() -> Vec<PathBuf> {
+    let Some(dir) = storage::data_path(LEVELS_DIR) else {
+        return Vec::new();
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!("Failed to read {}: {err}", dir.display());
+            }
+            return Vec::new();
+        }
+    };
+
+    let mut levels: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with(&format!(".{LEVEL_EXTENSION}")))
+        })
+        .collect();
+    levels.sort();
+    levels
+}
+
+/// Имя уровня, которое дизайнер сейчас набирает. Пока оно набирается, сочетания клавиш не работают.
+#[derive(Resource, Default)]
+struct NameDraft(String);
+
+#[derive(Component, Clone, Copy)]
+enum EditorAction {
+    Brush(LayoutCell),
+    Rename,
+    New,
+    Open(isize),
+    Undo,
+    Redo,
+    Width(isize),
+    Height(isize),
+    Moves(i32),
+    Score(isize),
+    Collect(isize),
+    TestPlay,
+    Save,
+    Back,
+}
+
+#[derive(Component, Clone, Copy)]
+enum EditorLabel {
+    Name,
+    Width,
+    Height,
+    Moves,
+    Score,
+    Collect,
+    Status,
+}
+
+#[derive(Component)]
+struct EditorGrid;
+
+#[derive(Component)]
+struct EditorCell(BoardIndex);
+
+fn spawn_editor(mut commands: Commands, mut editor: ResMut<LevelEditor>, theme: Res<ActiveTheme>) {
+    // Сетка строится заново при изменении редактора, в том числе при входе в него.
+    editor.set_changed();
+
+    commands
+        .spawn((
+            Node {
+                width: percent(100),
+                height: percent(100),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: px(24),
+                ..Default::default()
+            },
+            DespawnOnExit(AppState::Editor),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::px(3, CELL_SIZE),
+                    row_gap: px(4),
+                    column_gap: px(4),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for brush in PALETTE {
+                        parent.spawn((
+                            cell_node(brush, &theme),
+                            Interaction::default(),
+                            Outline::new(px(3), px(1), Color::NONE),
+                            EditorAction::Brush(brush),
+                        ));
+                    }
+                });
+
+            parent.spawn((
+                Node {
+                    display: Display::Grid,
+                    row_gap: px(2),
+                    column_gap: px(2),
+                    ..Default::default()
+                },
+                EditorGrid,
+            ));
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(8),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    tool_row(
+                        parent,
+                        Some(EditorLabel::Name),
+                        &[("editor-rename", EditorAction::Rename)],
+                    );
+                    tool_row(
+                        parent,
+                        None,
+                        &[
+                            ("editor-new", EditorAction::New),
+                            ("editor-previous", EditorAction::Open(-1)),
+                            ("editor-next", EditorAction::Open(1)),
+                        ],
+                    );
+                    tool_row(
+                        parent,
+                        None,
+                        &[
+                            ("editor-undo", EditorAction::Undo),
+                            ("editor-redo", EditorAction::Redo),
+                        ],
+                    );
+                    tool_row(
+                        parent,
+                        Some(EditorLabel::Width),
+                        &[
+                            ("editor-less", EditorAction::Width(-1)),
+                            ("editor-more", EditorAction::Width(1)),
+                        ],
+                    );
+                    tool_row(
+                        parent,
+                        Some(EditorLabel::Height),
+                        &[
+                            ("editor-less", EditorAction::Height(-1)),
+                            ("editor-more", EditorAction::Height(1)),
+                        ],
+                    );
+                    tool_row(
+                        parent,
+                        Some(EditorLabel::Moves),
+                        &[
+                            ("editor-less", EditorAction::Moves(-(MOVES_STEP as i32))),
+                            ("editor-more", EditorAction::Moves(MOVES_STEP as i32)),
+                        ],
+                    );
+                    tool_row(
+                        parent,
+                        Some(EditorLabel::Score),
+                        &[
+                            ("editor-less", EditorAction::Score(-(SCORE_STEP as isize))),
+                            ("editor-more", EditorAction::Score(SCORE_STEP as isize)),
+                        ],
+                    );
+                    tool_row(
+                        parent,
+                        Some(EditorLabel::Collect),
+                        &[
+                            (
+                                "editor-less",
+                                EditorAction::Collect(-(COLLECT_STEP as isize)),
+                            ),
+                            ("editor-more", EditorAction::Collect(COLLECT_STEP as isize)),
+                        ],
+                    );
+                    tool_row(
+                        parent,
+                        None,
+                        &[
+                            ("editor-test-play", EditorAction::TestPlay),
+                            ("editor-save", EditorAction::Save),
+                            ("menu-back", EditorAction::Back),
+                        ],
+                    );
+                    parent.spawn((label(""), EditorLabel::Status));
+                });
+        });
+}
+
+/// Строка панели: подпись со значением и кнопки, которые его меняют.
+fn tool_row(
+    parent: &mut ChildSpawnerCommands,
+    value: Option<EditorLabel>,
+    buttons: &[(&'static str, EditorAction)],
+) {
+    parent
+        .spawn(Node {
+            align_items: AlignItems::Center,
+            column_gap: px(8),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            if let Some(value) = value {
+                parent.spawn((
+                    label(""),
+                    value,
+                    Node {
+                        width: px(260),
+                        ..Default::default()
+                    },
+                ));
+            }
+            for (id, action) in buttons {
+                parent.spawn((
+                    Button,
+                    Node {
+                        min_width: px(54),
+                        height: px(44),
+                        padding: UiRect::horizontal(px(12)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    BackgroundColor(NORMAL_BUTTON),
+                    *action,
+                    children![localized_label(id)],
+                ));
+            }
+        });
+}
+
+/// Клетка редактора: цвет формы из темы и буква из текстовой записи доски.
+///
+/// У клеток нет `Button`, иначе подсветка кнопок меню перекрашивала бы их при наведении.
+fn cell_node(cell: LayoutCell, theme: &ActiveTheme) -> impl Bundle {
+    let color = match cell {
+        LayoutCell::Tile(form) | LayoutCell::Special(form) => theme.0.forms.get(form).color.0,
+        LayoutCell::Empty => theme.0.cell.0,
+        LayoutCell::Hole => Color::NONE,
+        LayoutCell::Blocker => BLOCKER_COLOR,
+    };
+
+    (
+        Node {
+            width: px(CELL_SIZE),
+            height: px(CELL_SIZE),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        BackgroundColor(color),
+        children![label(cell.symbol().to_string())],
+    )
+}
+
+fn rebuild_grid(
+    mut commands: Commands,
+    editor: Res<LevelEditor>,
+    theme: Res<ActiveTheme>,
+    grid: Single<(Entity, &mut Node), With<EditorGrid>>,
+) {
+    let (grid, mut node) = grid.into_inner();
+    let layout = &editor.level.layout;
+
+    node.grid_template_columns = RepeatedGridTrack::px(layout.width() as u16, CELL_SIZE);
+    commands.entity(grid).despawn_related::<Children>();
+    commands.entity(grid).with_children(|parent| {
+        // Верхний ряд записи идёт первым, как и в текстовой записи.
+        for row_id in (0..layout.height()).rev() {
+            for col_id in 0..layout.width() {
+                let idx = BoardIndex::from((row_id, col_id));
+                parent.spawn((
+                    cell_node(layout.get(idx), &theme),
+                    Interaction::default(),
+                    EditorCell(idx),
+                ));
+            }
+        }
+    });
+}
+
+/// Клетка красится нажатием или протягиванием с зажатой кнопкой мыши, мазок кончается с отпусканием.
+fn paint_cells(
+    buttons: Res<ButtonInput<MouseButton>>,
+    cells: Query<(&Interaction, &EditorCell), Changed<Interaction>>,
+    mut editor: ResMut<LevelEditor>,
+) {
+    if buttons.just_released(MouseButton::Left) {
+        editor.end_stroke();
+    }
+
+    for (interaction, EditorCell(idx)) in cells {
+        let painting = match interaction {
+            Interaction::Pressed => {
+                editor.begin_stroke();
+                true
+            }
+            Interaction::Hovered => buttons.pressed(MouseButton::Left),
+            Interaction::None => false,
+        };
+
+        if painting && editor.level.layout.get(*idx) != editor.brush {
+            editor.paint(*idx);
+        }
+    }
+}
+
+/// Enter переименовывает уровень набранным именем, Escape оставляет прежнее.
+fn type_level_name(
+    mut commands: Commands,
+    mut keyboard_input: MessageReader<KeyboardInput>,
+    mut draft: ResMut<NameDraft>,
+    mut editor: ResMut<LevelEditor>,
+) {
+    for input in keyboard_input.read() {
+        if input.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &input.logical_key {
+            Key::Enter => {
+                editor.set_name(&draft.0);
+                commands.remove_resource::<NameDraft>();
+                return;
+            }
+            Key::Escape => {
+                commands.remove_resource::<NameDraft>();
+                return;
+            }
+            Key::Backspace => {
+                draft.0.pop();
+            }
+            Key::Character(chars) => {
+                for char in chars.chars().filter(|char| !char.is_control()) {
+                    if draft.0.chars().count() < MAX_NAME_LEN {
+                        draft.0.push(char);
+                    }
+                }
+            }
+            Key::Space if draft.0.chars().count() < MAX_NAME_LEN => draft.0.push(' '),
+            _ => {}
+        }
+    }
+}
+
+fn finish_naming(mut commands: Commands) {
+    commands.remove_resource::<NameDraft>();
+}
+
+fn editor_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut editor: ResMut<LevelEditor>,
+    locale: Res<Locale>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let shift_pressed = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keys.just_pressed(KeyCode::KeyY) || shift_pressed && keys.just_pressed(KeyCode::KeyZ) {
+        editor.redo();
+    } else if keys.just_pressed(KeyCode::KeyZ) {
+        editor.undo();
+    } else if keys.just_pressed(KeyCode::KeyS) {
+        editor.save(&locale);
+    }
+}
+
+fn handle_editor_actions(
+    mut commands: Commands,
+    actions: Query<(&Interaction, &EditorAction), Changed<Interaction>>,
+    mut editor: ResMut<LevelEditor>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, action) in actions {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let layout = &editor.level.layout;
+        let (width, height) = (layout.width(), layout.height());
+
+        match *action {
+            EditorAction::Brush(brush) => editor.set_brush(brush),
+            EditorAction::Rename => {
+                commands.insert_resource(NameDraft(editor.level.name.clone()));
+            }
+            EditorAction::New => editor.open(blank_level()),
+            EditorAction::Open(step) => editor.open_next(step),
+            EditorAction::Undo => editor.undo(),
+            EditorAction::Redo => editor.redo(),
+            EditorAction::Width(step) => editor.resize(width.saturating_add_signed(step), height),
+            EditorAction::Height(step) => editor.resize(width, height.saturating_add_signed(step)),
+            EditorAction::Moves(step) => {
+                let moves = editor.level.moves.unwrap_or(0).saturating_add_signed(step);
+                editor.set_moves(Some(moves));
+            }
+            EditorAction::Score(step) => {
+                let score = editor.score_goal().unwrap_or(0).saturating_add_signed(step);
+                editor.set_score_goal(score);
+            }
+            EditorAction::Collect(step) => {
+                if let Some(form) = editor.brush.form() {
+                    let count = editor.collect_goal(form).saturating_add_signed(step);
+                    editor.set_collect_goal(form, count);
+                }
+            }
+            EditorAction::TestPlay => {
+                commands.insert_resource(editor.test_session(settings.animation_speed));
+                next_state.set(AppState::InGame);
+            }
+            EditorAction::Save => editor.save(&locale),
+            EditorAction::Back => {
+                commands.remove_resource::<LevelEditor>();
+                next_state.set(AppState::MainMenu);
+            }
+        }
+    }
+}
+
+fn display_editor(
+    editor: Res<LevelEditor>,
+    draft: Option<Res<NameDraft>>,
+    locale: Res<Locale>,
+    labels: Query<(&EditorLabel, &mut Text)>,
+    brushes: Query<(&EditorAction, &mut Outline)>,
+) {
+    let level = &editor.level;
+
+    for (label, mut text) in labels {
+        let value = match label {
+            EditorLabel::Name => match &draft {
+                Some(draft) => {
+                    locale.format("editor-name", &[("name", format!("{}_", draft.0).into())])
+                }
+                None => locale.format("editor-name", &[("name", level.name.clone().into())]),
+            },
+            EditorLabel::Width => {
+                locale.format("editor-width", &[("width", level.layout.width().into())])
+            }
+            EditorLabel::Height => {
+                locale.format("editor-height", &[("height", level.layout.height().into())])
+            }
+            EditorLabel::Moves => locale.format(
+                "editor-moves",
+                &[("moves", level.moves.unwrap_or(0).into())],
+            ),
+            EditorLabel::Score => locale.format(
+                "editor-score-goal",
+                &[("score", editor.score_goal().unwrap_or(0).into())],
+            ),
+            EditorLabel::Collect => match editor.brush.form() {
+                Some(form) => {
+                    let count = editor.collect_goal(form);
+                    locale.format(
+                        "editor-collect-goal",
+                        &[
+                            ("count", count.into()),
+                            ("form", form_name(&locale, form, count).into()),
+                        ],
+                    )
+                }
+                None => locale.text("editor-collect-pick"),
+            },
+            EditorLabel::Status => editor.status.clone().unwrap_or_default(),
+        };
+
+        if text.0 != value {
+            text.0 = value;
+        }
+    }
+
+    for (action, mut outline) in brushes {
+        if let EditorAction::Brush(brush) = action {
+            outline.color = if *brush == editor.brush {
+                BRUSH_COLOR
+            } else {
+                Color::NONE
+            };
+        }
+    }
+}
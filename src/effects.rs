@@ -0,0 +1,168 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{AppState, BoardAssets, board::Board, messages::MatchFound, settings::Settings};
+
+const SHARDS_PER_TILE: usize = 6;
+const SHARD_LIFETIME_SECS: f32 = 0.6;
+/// Скорость осколков и ускорение падения в клетках в секунду.
+const SHARD_SPEED: (f32, f32) = (2., 6.);
+const SHARD_GRAVITY: f32 = 12.;
+/// Ряд из трёх не трясёт экран, каждая фишка сверх этого добавляет тряски.
+const SHAKE_PER_EXTRA_TILE: f32 = 0.2;
+const SHAKE_DECAY_PER_SEC: f32 = 1.5;
+const MAX_SHAKE_OFFSET: f32 = 12.;
+
+/// Осколки на месте снятых фишек и тряска камеры на больших совпадениях.
+///
+/// Вспышка на всю доску ждёт особых фишек, которых пока нет.
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Shake>()
+            .add_systems(Startup, setup_shard_mesh)
+            .add_systems(OnExit(AppState::InGame), stop_shake)
+            .add_systems(
+                Update,
+                (
+                    spawn_shards.run_if(effects_enabled),
+                    update_shards,
+                    shake_camera,
+                ),
+            );
+    }
+}
+
+fn effects_enabled(settings: Res<Settings>) -> bool {
+    settings.effects
+}
+
+#[derive(Resource)]
+struct ShardMesh(Handle<Mesh>);
+
+fn setup_shard_mesh(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let mesh = meshes.add(Triangle2d::new(
+        Vec2::new(0., 0.5),
+        Vec2::new(-0.3, -0.5),
+        Vec2::new(0.3, -0.5),
+    ));
+
+    commands.insert_resource(ShardMesh(mesh));
+}
+
+/// Осколок снятой фишки. Скорость в пикселях в секунду.
+#[derive(Component)]
+pub struct Shard {
+    velocity: Vec2,
+    spin: f32,
+    lifetime: Timer,
+}
+
+/// Тряска камеры.
+#[derive(Resource, Default)]
+struct Shake {
+    /// Сила от 0 до 1. Смещение камеры растёт как её квадрат, поэтому слабая тряска почти не заметна.
+    strength: f32,
+    /// Где стояла камера до тряски. Камеру трясёт вокруг этой точки и возвращает в неё, когда тряска стихнет.
+    base: Option<Vec2>,
+}
+
+fn spawn_shards(
+    mut commands: Commands,
+    mut matches: MessageReader<MatchFound>,
+    mut shake: ResMut<Shake>,
+    boards: Query<&Board>,
+    board_assets: Res<BoardAssets>,
+    shard_mesh: Res<ShardMesh>,
+) {
+    let mut rng = rand::rng();
+
+    for found in matches.read() {
+        let Ok(board) = boards.get(found.board) else {
+            continue;
+        };
+        let shard_size = board.cell_size() * 0.2;
+        let (_, material) = board_assets.form(found.form);
+        let extra_tiles = found.cells.len().saturating_sub(3);
+        shake.strength = (shake.strength + SHAKE_PER_EXTRA_TILE * extra_tiles as f32).min(1.);
+
+        for idx in &found.cells {
+            let Vec2 { x, y } = board.get_cell_coord(*idx);
+
+            for _ in 0..SHARDS_PER_TILE {
+                let angle = rng.random_range(0. ..TAU);
+                let speed = rng.random_range(SHARD_SPEED.0..SHARD_SPEED.1) * board.cell_size();
+
+                commands.spawn((
+                    Mesh2d(shard_mesh.0.clone()),
+                    MeshMaterial2d(material.clone()),
+                    Transform::from_xyz(x, y, 200.)
+                        .with_rotation(Quat::from_rotation_z(angle))
+                        .with_scale(Vec3::splat(shard_size)),
+                    Shard {
+                        velocity: Vec2::from_angle(angle) * speed,
+                        spin: rng.random_range(-TAU..TAU),
+                        lifetime: Timer::from_seconds(SHARD_LIFETIME_SECS, TimerMode::Once),
+                    },
+                    DespawnOnExit(AppState::InGame),
+                ));
+            }
+        }
+    }
+}
+
+fn update_shards(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    shards: Query<(Entity, &mut Transform, &mut Shard)>,
+) {
+    let shard_size = Board::CELL_SIZE * 0.2;
+    let delta = time.delta().mul_f32(settings.animation_speed);
+
+    for (entity, mut transform, mut shard) in shards {
+        if shard.lifetime.tick(delta).is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        shard.velocity.y -= SHARD_GRAVITY * Board::CELL_SIZE * delta.as_secs_f32();
+        transform.translation += shard.velocity.extend(0.) * delta.as_secs_f32();
+        transform.rotate_z(shard.spin * delta.as_secs_f32());
+        transform.scale = Vec3::splat(shard_size * shard.lifetime.fraction_remaining());
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut shake: ResMut<Shake>,
+    mut camera: Single<&mut Transform, With<Camera2d>>,
+) {
+    if !settings.effects {
+        shake.strength = 0.;
+    }
+
+    let position = if shake.strength > 0. {
+        let base = *shake.base.get_or_insert(camera.translation.truncate());
+        let mut rng = rand::rng();
+        let direction = Vec2::from_angle(rng.random_range(0. ..TAU));
+        base + direction * MAX_SHAKE_OFFSET * shake.strength * shake.strength
+    } else if let Some(base) = shake.base.take() {
+        base
+    } else {
+        return;
+    };
+
+    camera.translation.x = position.x;
+    camera.translation.y = position.y;
+    let decay = SHAKE_DECAY_PER_SEC * settings.animation_speed * time.delta_secs();
+    shake.strength = (shake.strength - decay).max(0.);
+}
+
+fn stop_shake(mut shake: ResMut<Shake>) {
+    shake.strength = 0.;
+}
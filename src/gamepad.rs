@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppState, BoardAssets, GameSession, GameState, Selection,
+    board::{Board, BoardIndex},
+    net::remote_turn,
+    replay::Playback,
+    versus::Player,
+};
+
+/// Игра с геймпадов: крестовина водит курсор по доске, A выбирает фишку.
+///
+/// Геймпады по порядку подключения достаются хозяевам досок: в гонке первый играет за первого игрока,
+/// второй — за второго. Когда доска одна, все геймпады водят по ней один курсор.
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PadOrder>().add_systems(
+            Update,
+            (
+                track_gamepads,
+                (move_pad_cursors, draw_pad_cursors)
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(resource_exists::<Playback>))
+                    .run_if(not(remote_turn)),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Клетка под курсором геймпада на доске. Курсор появляется с первым нажатием крестовины.
+#[derive(Component)]
+pub struct PadCursor(pub BoardIndex);
+
+/// Рамка курсора геймпада на доске-сущности.
+#[derive(Component)]
+struct PadCursorFrame(Entity);
+
+/// Геймпады в порядке подключения. Номера сущностей переиспользуются, поэтому порядок по ним не годится.
+#[derive(Resource, Default)]
+struct PadOrder(Vec<Entity>);
+
+fn track_gamepads(
+    connected: Query<Entity, Added<Gamepad>>,
+    mut disconnected: RemovedComponents<Gamepad>,
+    mut order: ResMut<PadOrder>,
+) {
+    for entity in disconnected.read() {
+        order.0.retain(|pad| *pad != entity);
+    }
+    order.0.extend(connected.iter());
+}
+
+fn move_pad_cursors(
+    mut commands: Commands,
+    gamepads: Query<&Gamepad>,
+    order: Res<PadOrder>,
+    mut boards: Query<(
+        Entity,
+        &Board,
+        &mut Selection,
+        &Player,
+        Option<&mut PadCursor>,
+    )>,
+    session: Res<GameSession>,
+) {
+    let owners = session.mode.board_owners();
+
+    for (pad_id, pad) in order.0.iter().enumerate() {
+        let Ok(gamepad) = gamepads.get(*pad) else {
+            continue;
+        };
+        let player = match owners {
+            [owner] => *owner,
+            _ => match owners.get(pad_id) {
+                Some(owner) => *owner,
+                None => continue,
+            },
+        };
+        let Some((board_entity, board, mut selection, _, cursor)) = boards
+            .iter_mut()
+            .find(|(_, _, _, owner, _)| **owner == player)
+        else {
+            continue;
+        };
+
+        let step = [
+            (GamepadButton::DPadUp, (1, 0)),
+            (GamepadButton::DPadDown, (-1, 0)),
+            (GamepadButton::DPadLeft, (0, -1)),
+            (GamepadButton::DPadRight, (0, 1)),
+        ]
+        .into_iter()
+        .find_map(|(button, step)| gamepad.just_pressed(button).then_some(step));
+
+        let mut cell = cursor.as_ref().map(|cursor| cursor.0);
+        if let Some((row_step, col_step)) = step {
+            // Первое нажатие только показывает курсор в левом нижнем углу, как у клавиатуры.
+            let moved = match cell {
+                Some(cell) => (
+                    cell.row_id()
+                        .saturating_add_signed(row_step)
+                        .min(board.visible_height() - 1),
+                    cell.col_id()
+                        .saturating_add_signed(col_step)
+                        .min(board.width() - 1),
+                )
+                    .into(),
+                None => (0, 0).into(),
+            };
+
+            match cursor {
+                Some(mut cursor) => cursor.0 = moved,
+                None => {
+                    commands.entity(board_entity).insert(PadCursor(moved));
+                }
+            }
+            cell = Some(moved);
+        }
+
+        if gamepad.just_pressed(GamepadButton::South)
+            && !session.is_over()
+            && let Some(cell) = cell
+        {
+            selection.select(cell);
+        }
+    }
+}
+
+fn draw_pad_cursors(
+    mut commands: Commands,
+    boards: Query<(Entity, &Board, &PadCursor), Changed<PadCursor>>,
+    mut frames: Query<(&PadCursorFrame, &mut Transform)>,
+    board_assets: Res<BoardAssets>,
+) {
+    for (board_entity, board, cursor) in boards {
+        let Vec2 { x, y } = board.get_cell_coord(cursor.0);
+        let translation = Vec3::new(x, y, 0.4);
+
+        match frames.iter_mut().find(|(frame, _)| frame.0 == board_entity) {
+            Some((_, mut transform)) => transform.translation = translation,
+            None => {
+                commands.spawn((
+                    Mesh2d(board_assets.rectangle_mesh.clone()),
+                    MeshMaterial2d(board_assets.select_area_material.clone()),
+                    Transform::from_translation(translation)
+                        .with_scale(Vec3::splat(board.cell_size())),
+                    PadCursorFrame(board_entity),
+                    DespawnOnExit(AppState::InGame),
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use std::{io, path::PathBuf};
+
+use bevy::{
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameMode, GameOverState, GameSession, ScoreStorage,
+    editor::LevelEditor,
+    locale::Locale,
+    menu::{
+        MenuAction, MenuState, NORMAL_BUTTON, OVERLAY_COLOR, button, label, localized_label,
+        menu_button, screen_root, title,
+    },
+    replay::Playback,
+    storage::{self, StorageRoot},
+};
+
+const HIGH_SCORES_FILE: &str = "highscores.ron";
+const HIGH_SCORES_VERSION: u32 = 1;
+const TABLE_SIZE: usize = 10;
+const MAX_NAME_LEN: usize = 12;
+const DEFAULT_NAME: &str = "Player";
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        let root = app
+            .init_resource::<StorageRoot>()
+            .world()
+            .resource::<StorageRoot>();
+        let high_scores = HighScores::load(root);
+
+        app.insert_resource(high_scores)
+            .init_resource::<HighScoresView>()
+            .add_systems(OnEnter(GameOverState::NameEntry), spawn_name_entry)
+            .add_systems(OnEnter(MenuState::HighScores), spawn_high_scores_menu)
+            .add_systems(
+                Update,
+                (
+                    handle_name_input.run_if(in_state(GameOverState::NameEntry)),
+                    (
+                        select_high_scores_tab,
+                        step_high_scores_level,
+                        display_high_scores,
+                    )
+                        .chain()
+                        .run_if(in_state(MenuState::HighScores)),
+                ),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: usize,
+    pub date: NaiveDate,
+    pub moves: u32,
+    pub seed: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScoreTable {
+    mode: GameMode,
+    level: Option<String>,
+    entries: Vec<HighScoreEntry>,
+}
+
+#[derive(Resource, Serialize, Deserialize)]
+pub struct HighScores {
+    version: u32,
+    tables: Vec<ScoreTable>,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        Self {
+            version: HIGH_SCORES_VERSION,
+            tables: Vec::new(),
+        }
+    }
+}
+
+impl HighScores {
+    fn path(root: &StorageRoot) -> Option<PathBuf> {
+        root.data_path(HIGH_SCORES_FILE)
+    }
+
+    /// Загружает таблицу рекордов. Отсутствующий, испорченный или несовместимый файл даёт пустую таблицу.
+    pub fn load(root: &StorageRoot) -> Self {
+        let Some(path) = Self::path(root) else {
+            return Self::default();
+        };
+
+        match storage::read_ron::<Self>(&path) {
+            Ok(high_scores) if high_scores.version == HIGH_SCORES_VERSION => high_scores,
+            Ok(high_scores) => {
+                warn!(
+                    "Unsupported high scores version {} (expected {HIGH_SCORES_VERSION})",
+                    high_scores.version
+                );
+                storage::back_up(&path);
+                Self::default()
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                warn!("Failed to read high scores from {}: {err}", path.display());
+                storage::back_up(&path);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, root: &StorageRoot) -> io::Result<()> {
+        let path = Self::path(root).ok_or_else(|| io::Error::other("No data directory"))?;
+
+        storage::write_ron(&path, self)
+    }
+
+    pub fn entries(&self, mode: GameMode, level: Option<&str>) -> &[HighScoreEntry] {
+        self.tables
+            .iter()
+            .find(|table| table.mode == mode && table.level.as_deref() == level)
+            .map_or(&[], |table| &table.entries)
+    }
+
+    /// Уровни, для которых в режиме `mode` есть таблицы, по алфавиту.
+    pub fn levels(&self, mode: GameMode) -> Vec<&str> {
+        let mut levels = self
+            .tables
+            .iter()
+            .filter(|table| table.mode == mode)
+            .filter_map(|table| table.level.as_deref())
+            .collect::<Vec<_>>();
+        levels.sort_unstable();
+
+        levels
+    }
+
+    pub fn qualifies(&self, mode: GameMode, level: Option<&str>, score: usize) -> bool {
+        let entries = self.entries(mode, level);
+
+        mode.has_high_scores()
+            && score > 0
+            && (entries.len() < TABLE_SIZE || entries.last().is_some_and(|last| score > last.score))
+    }
+
+    pub fn insert(&mut self, mode: GameMode, level: Option<&str>, entry: HighScoreEntry) {
+        let table_id = match self
+            .tables
+            .iter()
+            .position(|table| table.mode == mode && table.level.as_deref() == level)
+        {
+            Some(table_id) => table_id,
+            None => {
+                self.tables.push(ScoreTable {
+                    mode,
+                    level: level.map(ToString::to_string),
+                    entries: Vec::new(),
+                });
+                self.tables.len() - 1
+            }
+        };
+        let entries = &mut self.tables[table_id].entries;
+
+        // При равных очках выше тот, кто потратил меньше ходов.
+        let rank = entries
+            .iter()
+            .position(|other| (entry.score, other.moves) > (other.score, entry.moves))
+            .unwrap_or(entries.len());
+
+        entries.insert(rank, entry);
+        entries.truncate(TABLE_SIZE);
+    }
+}
+
+#[derive(Component)]
+struct NameDisplay;
+
+/// Имя, которое игрок вводит для нового рекорда.
+#[derive(Resource, Default)]
+struct PlayerName(String);
+
+fn spawn_name_entry(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    playback: Option<Res<Playback>>,
+    editor: Option<Res<LevelEditor>>,
+    locale: Res<Locale>,
+    mut next_state: ResMut<NextState<GameOverState>>,
+) {
+    // Пересмотренная партия уже могла попасть в таблицу, второй раз её не записываем.
+    // За партию с автоигрой играл ИИ, а не игрок. Пробная игра из редактора идёт на недоделанном уровне.
+    if playback.is_some()
+        || editor.is_some()
+        || session.autoplayed
+        || !high_scores.qualifies(session.mode, session.level_name(), score.total())
+    {
+        next_state.set(GameOverState::Summary);
+        return;
+    }
+
+    commands.insert_resource(PlayerName::default());
+    commands.spawn((
+        screen_root(OVERLAY_COLOR),
+        DespawnOnExit(GameOverState::NameEntry),
+        children![
+            title("new-record-title"),
+            label(locale.format("score", &[("score", score.total().into())])),
+            localized_label("enter-name"),
+            (label("_"), NameDisplay),
+        ],
+    ));
+}
+
+fn handle_name_input(
+    mut keyboard_input: MessageReader<KeyboardInput>,
+    mut name: ResMut<PlayerName>,
+    mut display: Single<&mut Text, With<NameDisplay>>,
+    mut high_scores: ResMut<HighScores>,
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    root: Res<StorageRoot>,
+    mut next_state: ResMut<NextState<GameOverState>>,
+) {
+    for input in keyboard_input.read() {
+        if input.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &input.logical_key {
+            Key::Enter => {
+                let name = name.0.trim();
+                let entry = HighScoreEntry {
+                    name: if name.is_empty() { DEFAULT_NAME } else { name }.to_string(),
+                    score: score.total(),
+                    date: Local::now().date_naive(),
+                    moves: session.moves_made,
+                    seed: session.seed,
+                };
+
+                high_scores.insert(session.mode, session.level_name(), entry);
+                if let Err(err) = high_scores.save(&root) {
+                    error!("Failed to save high scores: {err}");
+                }
+
+                next_state.set(GameOverState::Summary);
+                return;
+            }
+            Key::Backspace => {
+                name.0.pop();
+            }
+            Key::Character(chars) => {
+                for char in chars.chars().filter(|char| !char.is_control()) {
+                    if name.0.chars().count() < MAX_NAME_LEN {
+                        name.0.push(char);
+                    }
+                }
+            }
+            Key::Space if name.0.chars().count() < MAX_NAME_LEN => name.0.push(' '),
+            _ => {}
+        }
+    }
+
+    if name.is_changed() {
+        display.0 = format!("{}_", name.0);
+    }
+}
+
+/// Режим и уровень, таблица которых показана на экране рекордов.
+#[derive(Resource, Default)]
+struct HighScoresView {
+    mode: GameMode,
+    level: Option<String>,
+}
+
+impl HighScoresView {
+    /// Таблицы, которые можно листать во вкладке режима: обычная доска, а за ней уровни.
+    /// Кампания без уровня не играется, поэтому у неё только уровни.
+    fn choices(&self, high_scores: &HighScores) -> Vec<Option<String>> {
+        let standard = (self.mode != GameMode::Campaign).then_some(None);
+
+        standard
+            .into_iter()
+            .chain(
+                high_scores
+                    .levels(self.mode)
+                    .into_iter()
+                    .map(|level| Some(level.to_string())),
+            )
+            .collect()
+    }
+}
+
+#[derive(Component)]
+struct HighScoresTab(GameMode);
+
+/// Кнопка, листающая таблицы уровней на шаг назад или вперёд.
+#[derive(Component)]
+struct HighScoresLevelStep(isize);
+
+#[derive(Component)]
+struct HighScoresLevelName;
+
+#[derive(Component)]
+struct HighScoresTable;
+
+fn spawn_high_scores_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            screen_root(Color::NONE),
+            DespawnOnExit(MenuState::HighScores),
+        ))
+        .with_children(|parent| {
+            parent.spawn(title("high-scores-title"));
+
+            parent
+                .spawn(Node {
+                    column_gap: px(12),
+                    ..Default::default()
+                })
+                .with_children(|tabs| {
+                    for mode in GameMode::ALL
+                        .into_iter()
+                        .chain([GameMode::Campaign])
+                        .filter(GameMode::has_high_scores)
+                    {
+                        tabs.spawn((button(mode.message_id()), HighScoresTab(mode)));
+                    }
+                });
+
+            parent
+                .spawn(Node {
+                    column_gap: px(12),
+                    align_items: AlignItems::Center,
+                    margin: UiRect::top(px(12)),
+                    ..Default::default()
+                })
+                .with_children(|row| {
+                    row.spawn(level_step_button("<", -1));
+                    row.spawn((
+                        label(""),
+                        Node {
+                            width: px(320),
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        TextLayout::new_with_justify(Justify::Center),
+                        HighScoresLevelName,
+                    ));
+                    row.spawn(level_step_button(">", 1));
+                });
+
+            parent.spawn((
+                Node {
+                    display: Display::Grid,
+                    grid_template_columns: vec![
+                        GridTrack::px(40.),
+                        GridTrack::px(200.),
+                        GridTrack::px(110.),
+                        GridTrack::px(90.),
+                        GridTrack::px(150.),
+                    ],
+                    row_gap: px(6),
+                    margin: UiRect::vertical(px(12)),
+                    min_height: px(330),
+                    align_content: AlignContent::Start,
+                    ..Default::default()
+                },
+                HighScoresTable,
+            ));
+
+            parent.spawn(menu_button("menu-back", MenuAction::Back));
+        });
+}
+
+fn level_step_button(text: &'static str, step: isize) -> impl Bundle {
+    (
+        Button,
+        Node {
+            width: px(54),
+            height: px(44),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        BackgroundColor(NORMAL_BUTTON),
+        HighScoresLevelStep(step),
+        children![label(text)],
+    )
+}
+
+/// Во вкладке режима сначала открывается первая из его таблиц.
+fn select_high_scores_tab(
+    tabs: Query<(&Interaction, &HighScoresTab), Changed<Interaction>>,
+    high_scores: Res<HighScores>,
+    mut view: ResMut<HighScoresView>,
+) {
+    for (interaction, tab) in tabs {
+        if *interaction == Interaction::Pressed {
+            view.mode = tab.0;
+            view.level = view.choices(&high_scores).into_iter().next().flatten();
+        }
+    }
+}
+
+fn step_high_scores_level(
+    buttons: Query<(&Interaction, &HighScoresLevelStep), Changed<Interaction>>,
+    high_scores: Res<HighScores>,
+    mut view: ResMut<HighScoresView>,
+) {
+    for (interaction, HighScoresLevelStep(step)) in buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let choices = view.choices(&high_scores);
+        if choices.is_empty() {
+            continue;
+        }
+        let current = choices
+            .iter()
+            .position(|level| *level == view.level)
+            .unwrap_or(0);
+        let next = (current as isize + step).rem_euclid(choices.len() as isize) as usize;
+        view.level = choices[next].clone();
+    }
+}
+
+fn display_high_scores(
+    mut commands: Commands,
+    view: Res<HighScoresView>,
+    high_scores: Res<HighScores>,
+    table: Single<Entity, With<HighScoresTable>>,
+    mut level_name: Single<&mut Text, With<HighScoresLevelName>>,
+    added: Query<(), Added<HighScoresTable>>,
+    locale: Res<Locale>,
+) {
+    if !view.is_changed() && !locale.is_changed() && added.is_empty() {
+        return;
+    }
+
+    let entries = high_scores.entries(view.mode, view.level.as_deref());
+    level_name.0 = match &view.level {
+        Some(level) => level.clone(),
+        None if view.mode == GameMode::Campaign => String::new(),
+        None => locale.text("high-scores-standard"),
+    };
+
+    commands
+        .entity(*table)
+        .despawn_children()
+        .with_children(|table| {
+            for header in [
+                "header-rank",
+                "header-name",
+                "header-score",
+                "header-moves",
+                "header-date",
+            ] {
+                table.spawn(localized_label(header));
+            }
+
+            if entries.is_empty() {
+                table.spawn((
+                    localized_label("no-records"),
+                    Node {
+                        grid_column: GridPlacement::span(5),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            for (rank, entry) in entries.iter().enumerate() {
+                table.spawn(label((rank + 1).to_string()));
+                table.spawn(label(entry.name.clone()));
+                table.spawn(label(entry.score.to_string()));
+                table.spawn(label(entry.moves.to_string()));
+                table.spawn(label(entry.date.to_string()));
+            }
+        });
+}
@@ -0,0 +1,115 @@
+use std::{io, path::Path};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{BoardLayout, Form},
+    storage,
+};
+
+/// Расширение файлов уровней.
+pub const LEVEL_EXTENSION: &str = "level.ron";
+
+/// Уровень: стартовая доска, лимит ходов и цели. Хранится в RON, доска в текстовой записи `BoardLayout`.
+///
+/// Запись кладётся в нижний левый угол доски, клетки вне записи и клетки без фишек заполняются случайно.
+#[derive(Asset, TypePath, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Level {
+    /// По имени уровня ведутся его рекорды, поэтому в кампании имена не повторяются.
+    pub name: String,
+    pub layout: BoardLayout,
+    /// Лимит ходов, `None` — без ограничений.
+    #[serde(default)]
+    pub moves: Option<u32>,
+    /// Уровень пройден, когда выполнены все цели. Без целей его играют до конца ходов.
+    #[serde(default)]
+    pub goals: Vec<LevelGoal>,
+    /// Очки на вторую, третью и следующие звёзды. Первую звезду даёт само прохождение.
+    #[serde(default)]
+    pub star_scores: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum LevelGoal {
+    /// Набрать столько очков.
+    Score(usize),
+    /// Снять с доски столько фишек формы.
+    Collect { form: Form, count: usize },
+}
+
+impl Level {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        storage::read_ron(path)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        storage::write_ron(path, self)
+    }
+
+    pub fn goals_met(&self, score: usize, collected: &Collected) -> bool {
+        !self.goals.is_empty() && self.goals.iter().all(|goal| goal.is_met(score, collected))
+    }
+
+    /// Звёзды за пройденный уровень.
+    pub fn stars(&self, score: usize) -> u8 {
+        1 + self
+            .star_scores
+            .iter()
+            .filter(|star_score| score >= **star_score)
+            .count() as u8
+    }
+
+    pub fn max_stars(&self) -> u8 {
+        1 + self.star_scores.len() as u8
+    }
+}
+
+impl LevelGoal {
+    pub fn is_met(&self, score: usize, collected: &Collected) -> bool {
+        match self {
+            LevelGoal::Score(target) => score >= *target,
+            LevelGoal::Collect { form, count } => collected.of(*form) >= *count,
+        }
+    }
+}
+
+/// Сколько фишек каждой формы снято за партию.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Collected([usize; Form::ALL.len()]);
+
+impl Collected {
+    pub fn of(&self, form: Form) -> usize {
+        self.0[form as usize]
+    }
+
+    pub(crate) fn add(&mut self, form: Form, count: usize) {
+        self.0[form as usize] += count;
+    }
+}
+
+/// Загружает файлы уровней как ассеты, например для кампании.
+#[derive(Default)]
+pub(crate) struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Level, Self::Error> {
+        storage::read_ron_asset(reader).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[LEVEL_EXTENSION]
+    }
+}
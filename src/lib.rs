@@ -0,0 +1,1623 @@
+use bevy::{math::prelude::*, prelude::*};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+pub mod accessibility;
+pub mod ai;
+pub mod audio;
+pub mod autoplay;
+pub mod board;
+pub mod campaign;
+pub mod daily;
+pub mod editor;
+pub mod effects;
+pub mod gamepad;
+pub mod highscores;
+pub mod level;
+pub mod locale;
+mod menu;
+pub mod messages;
+pub mod net;
+pub mod race;
+mod replay;
+pub mod rules;
+pub mod savegame;
+pub mod settings;
+pub mod storage;
+pub mod theme;
+mod undo;
+pub mod versus;
+
+use accessibility::{AccessibilityPlugin, AccessibilitySettings, form_name, glyph_mesh};
+use audio::GameAudioPlugin;
+use autoplay::{Autoplay, AutoplayPlugin};
+use board::{Board, BoardIndex, BoardLayout, Cell, CellKind, Form, TILE_VELOCITY, Tile};
+use campaign::CampaignPlugin;
+use chrono::NaiveDate;
+use daily::{DAILY_MOVES, DailyPlugin, daily_seed, today};
+use editor::EditorPlugin;
+use effects::EffectsPlugin;
+use gamepad::GamepadPlugin;
+use highscores::HighScoresPlugin;
+use level::{Collected, Level, LevelGoal};
+use locale::{Locale, LocalePlugin};
+use menu::MenuPlugin;
+use messages::{
+    BoardSettled, CursorMoved, MatchFound, ScoreChanged, SwapRejected, SwapRequested, TilesCleared,
+    TilesFell,
+};
+use net::{NetPlugin, remote_turn};
+use race::{RACE_SECONDS, RACE_TARGET_SCORE, board_origin, fit_camera, race_lines};
+use replay::{Playback, ReplayPlugin, ReplayRecorder};
+use rules::{Grid, SCORE_PER_TILE, group_runs};
+use savegame::{SaveGamePlugin, SavedGame};
+use settings::{Settings, SettingsPlugin};
+use storage::StorageRoot;
+use theme::{ActiveTheme, FormLook, Theme, ThemePlugin};
+use undo::{UndoHistory, UndoPlugin};
+use versus::{Player, VERSUS_ROUNDS, Versus, end_turn, grant_extra_turn};
+
+/// Вся игра: правила, меню, сохранения, повторы. Окно и рендер добавляет `main`.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            SettingsPlugin,
+            GameplayPlugin,
+            MenuPlugin,
+            HighScoresPlugin,
+            SaveGamePlugin,
+            UndoPlugin,
+            ReplayPlugin,
+            AutoplayPlugin,
+            GameAudioPlugin,
+            EffectsPlugin,
+            ThemePlugin,
+            AccessibilityPlugin,
+            LocalePlugin,
+            NetPlugin,
+            (EditorPlugin, DailyPlugin, CampaignPlugin, GamepadPlugin),
+        ))
+        .add_systems(Startup, setup_camera)
+        .add_systems(OnEnter(AppState::InGame), fit_camera)
+        .add_systems(
+            Update,
+            (
+                toggle_pause.run_if(in_state(AppState::InGame)),
+                display_score.run_if(in_state(AppState::InGame)),
+                dump_board.run_if(in_state(AppState::InGame)),
+                handle_click
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(resource_exists::<Playback>))
+                    .run_if(not(remote_turn)),
+            ),
+        );
+    }
+}
+
+/// Состояния, ресурсы и цепочка систем доски без ввода, интерфейса и работы с диском.
+///
+/// Этого достаточно, чтобы гонять партию в `App` с `MinimalPlugins`, например в тестах.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AppState>()
+            .add_sub_state::<GameState>()
+            .add_sub_state::<GameOverState>()
+            .init_resource::<ScoreStorage>()
+            .init_resource::<GameSession>()
+            .init_resource::<GameTick>()
+            .init_resource::<ReportedScore>()
+            .init_resource::<UndoHistory>()
+            .init_resource::<ActiveTheme>()
+            .init_resource::<Settings>()
+            .init_resource::<StorageRoot>()
+            .init_resource::<Locale>()
+            .add_message::<SwapRequested>()
+            .add_message::<SwapRejected>()
+            .add_message::<MatchFound>()
+            .add_message::<TilesCleared>()
+            .add_message::<TilesFell>()
+            .add_message::<BoardSettled>()
+            .add_message::<ScoreChanged>()
+            .add_message::<CursorMoved>()
+            .add_systems(OnEnter(AppState::Loading), finish_loading)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (setup, setup_score, reset_reported_score),
+            )
+            .add_systems(OnExit(AppState::InGame), teardown)
+            .add_systems(OnEnter(GameState::Paused), pause_time)
+            .add_systems(OnExit(GameState::Paused), unpause_time)
+            .add_systems(
+                FixedUpdate,
+                (
+                    advance_tick,
+                    tick_session,
+                    handle_selection,
+                    (
+                        move_tiles,
+                        check_swapped_for_matching,
+                        check_board_for_matching,
+                        grant_extra_turn,
+                        despawn_tiles,
+                        spawn_tiles,
+                    )
+                        .chain(),
+                    end_turn.run_if(condition_changed_to(true, board_settled)),
+                    (
+                        // Игра заканчивается только когда доска успокоилась, чтобы каскады успели досчитаться.
+                        check_game_over.run_if(board_settled),
+                        notify_board_settled.run_if(condition_changed_to(true, board_settled)),
+                    ),
+                )
+                    .chain()
+                    .in_set(GameplaySystems)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    notify_score_changed,
+                    restyle_board
+                        .run_if(resource_changed::<ActiveTheme>.or(board_style_changed))
+                        .run_if(in_state(AppState::InGame)),
+                ),
+            );
+    }
+}
+
+/// Цепочка систем, которая двигает и пересчитывает доску.
+///
+/// Работает в `FixedUpdate`, чтобы партия с тем же зерном и теми же ходами на тех же тиках
+/// проходила одинаково независимо от частоты кадров.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+struct GameplaySystems;
+
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    /// Промежуточное состояние, через которое партия перезапускается.
+    Loading,
+    InGame,
+    /// Редактор уровней.
+    Editor,
+}
+
+#[derive(SubStates, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[source(AppState = AppState::InGame)]
+pub enum GameState {
+    #[default]
+    Playing,
+    Paused,
+    GameOver,
+}
+
+#[derive(SubStates, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[source(GameState = GameState::GameOver)]
+pub enum GameOverState {
+    #[default]
+    NameEntry,
+    Summary,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    Moves,
+    Timed,
+    /// Двое по очереди за одной доской.
+    Versus,
+    /// Двое одновременно, каждый на своей доске из одного зерна.
+    Race,
+    /// Испытание дня: доска из зерна по дате и общий для всех запас ходов.
+    Daily,
+    /// Уровень кампании. Начинается с карты кампании, поэтому отдельной кнопки в меню у режима нет.
+    Campaign,
+}
+
+impl GameMode {
+    const ALL: [GameMode; 6] = [
+        GameMode::Classic,
+        GameMode::Moves,
+        GameMode::Timed,
+        GameMode::Versus,
+        GameMode::Race,
+        GameMode::Daily,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Moves => "Moves",
+            GameMode::Timed => "Timed",
+            GameMode::Versus => "Versus",
+            GameMode::Race => "Race",
+            GameMode::Daily => "Daily",
+            GameMode::Campaign => "Campaign",
+        }
+    }
+
+    fn message_id(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "mode-classic",
+            GameMode::Moves => "mode-moves",
+            GameMode::Timed => "mode-timed",
+            GameMode::Versus => "mode-versus",
+            GameMode::Race => "mode-race",
+            GameMode::Daily => "mode-daily",
+            GameMode::Campaign => "mode-campaign",
+        }
+    }
+
+    /// Двое играют друг против друга, а не на рекорд.
+    fn is_duel(&self) -> bool {
+        matches!(self, GameMode::Versus | GameMode::Race)
+    }
+
+    /// Поединок и гонка не попадают в рекорды: очки в них делятся на двоих.
+    /// У испытания дня каждый день своя доска, его лучшие счета хранятся по дням отдельно.
+    fn has_high_scores(&self) -> bool {
+        !self.is_duel() && *self != GameMode::Daily
+    }
+
+    /// Хозяева досок партии: в гонке у каждого игрока своя доска, в остальных режимах доска одна.
+    fn board_owners(&self) -> &'static [Player] {
+        match self {
+            GameMode::Race => &Player::ALL,
+            _ => &[Player::One],
+        }
+    }
+
+    fn move_limit(&self) -> Option<u32> {
+        match self {
+            GameMode::Moves => Some(MOVES_MODE_LIMIT),
+            GameMode::Daily => Some(DAILY_MOVES),
+            _ => None,
+        }
+    }
+
+    fn time_limit(&self) -> Option<f32> {
+        match self {
+            GameMode::Timed => Some(TIMED_MODE_SECONDS),
+            GameMode::Race => Some(RACE_SECONDS),
+            _ => None,
+        }
+    }
+
+    fn target_score(&self) -> Option<usize> {
+        match self {
+            GameMode::Race => Some(RACE_TARGET_SCORE),
+            _ => None,
+        }
+    }
+
+    /// Сколько раз за партию можно отменить ход, `None` — без ограничений.
+    ///
+    /// В поединке отмена вернула бы ход сопернику, а в гонке у каждого своя доска, поэтому отмены там нет.
+    /// В испытании дня все играют с одним запасом ходов, и отмена дала бы лишние попытки.
+    fn undo_limit(&self) -> Option<u32> {
+        match self {
+            GameMode::Classic => None,
+            GameMode::Moves | GameMode::Campaign => Some(MOVES_MODE_UNDO_LIMIT),
+            GameMode::Timed | GameMode::Versus | GameMode::Race | GameMode::Daily => Some(0),
+        }
+    }
+}
+
+const MOVES_MODE_LIMIT: u32 = 30;
+const MOVES_MODE_UNDO_LIMIT: u32 = 3;
+const TIMED_MODE_SECONDS: f32 = 120.;
+/// Тёмные значки видны на всех цветах форм, даже самых светлых.
+const GLYPH_COLOR: Color = Color::srgb(0.08, 0.08, 0.1);
+/// Преграда серая в любой теме, чтобы её не путали с фишкой.
+pub(crate) const BLOCKER_COLOR: Color = Color::srgb(0.45, 0.42, 0.4);
+
+#[derive(Resource)]
+pub struct GameSession {
+    mode: GameMode,
+    /// Уровень, который играют в режиме `mode`. Партии в стандартных режимах идут без уровня.
+    level: Option<Level>,
+    /// Путь уровня из манифеста кампании, по нему ведётся прогресс.
+    level_path: Option<String>,
+    collected: Collected,
+    /// День испытания, из него получено зерно.
+    day: Option<NaiveDate>,
+    /// День, когда партию начали. Испытание засчитывается, только если его начали в свой день.
+    started_on: NaiveDate,
+    seed: u64,
+    moves_made: u32,
+    moves_left: Option<u32>,
+    time_left: Option<Timer>,
+    undos_left: Option<u32>,
+    /// Скорость анимации на всю партию. От неё зависит, на каком тике успокоится доска,
+    /// поэтому повтор и сохранение хранят её, а смена в настройках действует со следующей партии.
+    animation_speed: f32,
+    versus: Option<Versus>,
+    /// Очки, с которыми игрок сразу выигрывает.
+    target_score: Option<usize>,
+    target_reached: bool,
+    /// Кто первым набрал цель гонки. Каскад соперника ещё может добавить ему очков, но победу уже не отнимет.
+    first_to_target: Option<Player>,
+    /// Хотя бы один ход сделала автоигра. Отмена хода этого не стирает, и в рекорды партия уже не попадёт.
+    autoplayed: bool,
+}
+
+impl Default for GameSession {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::default(),
+            level: None,
+            level_path: None,
+            collected: Collected::default(),
+            day: None,
+            started_on: today(),
+            seed: 0,
+            moves_made: 0,
+            moves_left: None,
+            time_left: None,
+            undos_left: None,
+            animation_speed: 1.,
+            versus: None,
+            target_score: None,
+            target_reached: false,
+            first_to_target: None,
+            autoplayed: false,
+        }
+    }
+}
+
+impl GameSession {
+    pub fn new(mode: GameMode, animation_speed: f32) -> Self {
+        let day = (mode == GameMode::Daily).then(today);
+
+        Self {
+            mode,
+            level: None,
+            level_path: None,
+            collected: Collected::default(),
+            day,
+            started_on: today(),
+            seed: day.map_or_else(rand::random, daily_seed),
+            moves_made: 0,
+            moves_left: mode.move_limit(),
+            time_left: mode
+                .time_limit()
+                .map(|secs| Timer::from_seconds(secs, TimerMode::Once)),
+            undos_left: mode.undo_limit(),
+            animation_speed,
+            versus: (mode == GameMode::Versus).then(Versus::default),
+            target_score: mode.target_score(),
+            target_reached: false,
+            first_to_target: None,
+            autoplayed: false,
+        }
+    }
+
+    /// Та же партия с заданным зерном, например присланным соперником по сети.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Испытание другого дня, например прошедшего.
+    pub fn with_day(mut self, day: NaiveDate) -> Self {
+        self.day = Some(day);
+        self.seed = daily_seed(day);
+        self
+    }
+
+    pub fn day(&self) -> Option<NaiveDate> {
+        self.day
+    }
+
+    /// Та же партия, начатая в другой день, например продолженная из сохранения.
+    pub fn with_start_day(mut self, day: NaiveDate) -> Self {
+        self.started_on = day;
+        self
+    }
+
+    pub fn started_on(&self) -> NaiveDate {
+        self.started_on
+    }
+
+    pub fn autoplayed(&self) -> bool {
+        self.autoplayed
+    }
+
+    pub fn first_to_target(&self) -> Option<Player> {
+        self.first_to_target
+    }
+
+    /// Та же партия на уровне: его лимит ходов заменяет лимит режима.
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.moves_left = level.moves.or(self.moves_left);
+        self.level = Some(level);
+        self
+    }
+
+    /// Та же партия на уровне кампании из файла `path`.
+    pub fn with_level_path(mut self, path: String) -> Self {
+        self.level_path = Some(path);
+        self
+    }
+
+    pub fn level(&self) -> Option<&Level> {
+        self.level.as_ref()
+    }
+
+    pub fn level_path(&self) -> Option<&str> {
+        self.level_path.as_deref()
+    }
+
+    fn level_name(&self) -> Option<&str> {
+        self.level.as_ref().map(|level| level.name.as_str())
+    }
+
+    /// Все цели уровня выполнены.
+    pub(crate) fn level_cleared(&self) -> bool {
+        self.level.is_some() && self.target_reached
+    }
+
+    pub fn versus(&self) -> Option<&Versus> {
+        self.versus.as_ref()
+    }
+
+    /// Игрок, которому идут очки за доску `owner`. В поединке это тот, чей сейчас ход.
+    fn scorer(&self, owner: Player) -> Player {
+        self.versus.map_or(owner, |versus| versus.player())
+    }
+
+    /// Хозяин доски, которой управляют с клавиатуры. В гонке мышь у первого игрока, клавиатура у второго,
+    /// а с двумя геймпадами у каждого свой, см. `GamepadPlugin`.
+    pub fn keyboard_player(&self) -> Player {
+        match self.mode {
+            GameMode::Race => Player::Two,
+            _ => Player::One,
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.moves_left == Some(0)
+            || self.time_left.as_ref().is_some_and(Timer::is_finished)
+            || self.versus.is_some_and(|versus| versus.is_over())
+            || self.target_reached
+    }
+}
+
+/// ГПСЧ доски. В гонке у досок одно зерно, поэтому они начинаются одинаково.
+#[derive(Component)]
+struct GameRng(ChaCha8Rng);
+
+/// Номер тика `FixedUpdate` с начала партии.
+#[derive(Resource, Default)]
+struct GameTick(u32);
+
+fn advance_tick(mut tick: ResMut<GameTick>) {
+    tick.0 += 1;
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2d);
+}
+
+fn tick_session(time: Res<Time>, mut session: ResMut<GameSession>) {
+    if let Some(timer) = session.time_left.as_mut() {
+        timer.tick(time.delta());
+    }
+}
+
+/// Все доски успокоились: ничего не движется, нет непроверенных обменов и совпадений, ждущих удаления.
+fn board_settled(
+    boards: Query<&TilesToDespawn>,
+    busy_tiles: Query<(), Or<(With<Moving>, With<CheckMatchesOrSwap>)>>,
+) -> bool {
+    boards
+        .iter()
+        .all(|tiles_to_despawn| tiles_to_despawn.0.is_empty())
+        && busy_tiles.is_empty()
+}
+
+fn notify_board_settled(mut settled: MessageWriter<BoardSettled>) {
+    settled.write(BoardSettled);
+}
+
+/// Счёт, о котором уже сообщено. В начале партии это счёт, с которым она началась,
+/// поэтому продолженная партия не сообщает о восстановленном счёте как о набранном.
+#[derive(Resource, Default)]
+struct ReportedScore(usize);
+
+fn reset_reported_score(score: Res<ScoreStorage>, mut reported: ResMut<ReportedScore>) {
+    reported.0 = score.total();
+}
+
+/// Счёт меняют несколько систем, поэтому сообщение шлёт одна, которая следит за ресурсом.
+fn notify_score_changed(
+    score: Res<ScoreStorage>,
+    mut reported: ResMut<ReportedScore>,
+    mut score_changed: MessageWriter<ScoreChanged>,
+) {
+    if score.total() != reported.0 {
+        score_changed.write(ScoreChanged {
+            previous: reported.0,
+            score: score.total(),
+        });
+        reported.0 = score.total();
+    }
+}
+
+fn check_game_over(session: Res<GameSession>, mut next_state: ResMut<NextState<GameState>>) {
+    if session.is_over() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    match state.get() {
+        GameState::Playing => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::Playing),
+        GameState::GameOver => {}
+    }
+}
+
+/// Отладка: печатает доску вместе со скрытыми рядами в текстовой записи.
+fn dump_board(keys: Res<ButtonInput<KeyCode>>, boards: Query<&Board>) {
+    if keys.just_pressed(KeyCode::F3) {
+        for board in boards {
+            println!("{board}");
+        }
+    }
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+/// Удаляет все сущности партии вместе с досками и сбрасывает её ресурсы, после чего `setup` может построить доски заново.
+fn teardown(
+    mut commands: Commands,
+    entities: Query<Entity, With<DespawnOnExit<AppState>>>,
+    mut score: ResMut<ScoreStorage>,
+    mut undo_history: ResMut<UndoHistory>,
+    mut tick: ResMut<GameTick>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    for entity in entities {
+        commands.entity(entity).try_despawn();
+    }
+
+    *score = ScoreStorage::default();
+    *undo_history = UndoHistory::default();
+    tick.0 = 0;
+    time.unpause();
+}
+
+fn restart(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+    session: Res<GameSession>,
+    settings: Res<Settings>,
+    playback: Option<ResMut<Playback>>,
+) {
+    // Повтор перезапускается с тем же зерном и с первого действия.
+    match playback {
+        Some(mut playback) => {
+            commands.insert_resource(playback.session());
+            playback.rewind();
+        }
+        None => {
+            // Уровень и испытание дня переигрываются с начала, а обычная партия начинается с новым зерном.
+            let mut next = GameSession::new(session.mode, settings.animation_speed);
+            if let Some(day) = session.day {
+                next = next.with_day(day);
+            }
+            if let Some(level) = session.level.clone() {
+                next = next.with_level(level);
+            }
+            next.level_path.clone_from(&session.level_path);
+            commands.insert_resource(next);
+        }
+    }
+
+    next_state.set(AppState::Loading);
+}
+
+fn finish_loading(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InGame);
+}
+
+fn setup_score(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 25.,
+            ..Default::default()
+        },
+        TextColor(Color::srgb(0.5, 0.5, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(5),
+            left: px(5),
+            ..Default::default()
+        },
+        ScoreDisplay,
+        DespawnOnExit(AppState::InGame),
+    ));
+}
+
+#[derive(Component)]
+struct ScoreDisplay;
+
+/// Очки по игрокам. Вне поединка все очки у первого игрока.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScoreStorage([usize; 2]);
+
+impl ScoreStorage {
+    pub fn solo(score: usize) -> Self {
+        Self([score, 0])
+    }
+
+    /// Очки обоих игроков в порядке `Player::ALL`.
+    pub fn new(scores: [usize; 2]) -> Self {
+        Self(scores)
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+
+    pub fn of(&self, player: Player) -> usize {
+        self.0[player as usize]
+    }
+
+    /// Кто набрал больше очков, `None` при ничьей.
+    pub fn leader(&self) -> Option<Player> {
+        match self.of(Player::One).cmp(&self.of(Player::Two)) {
+            std::cmp::Ordering::Greater => Some(Player::One),
+            std::cmp::Ordering::Less => Some(Player::Two),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    fn add(&mut self, player: Player, points: usize) {
+        self.0[player as usize] += points;
+    }
+}
+
+fn display_score(
+    score: Res<ScoreStorage>,
+    session: Res<GameSession>,
+    autoplay: Res<Autoplay>,
+    locale: Res<Locale>,
+    mut display: Single<&mut Text, With<ScoreDisplay>>,
+) {
+    let mut lines = match session.versus {
+        Some(versus) => versus_lines(&versus, &score, &locale),
+        None if session.mode == GameMode::Race => race_lines(&score, &locale),
+        None => vec![locale.format("score", &[("score", score.total().into())])],
+    };
+
+    if let Some(day) = session.day {
+        lines.push(locale.format("hud-daily", &[("date", day.to_string().into())]));
+    }
+    if let Some(level) = session.level.as_ref() {
+        lines.extend(goal_lines(level, &session.collected, &locale));
+    }
+
+    if let Some(moves_left) = session.moves_left {
+        lines.push(locale.format("hud-moves", &[("moves", moves_left.into())]));
+    }
+    if let Some(timer) = session.time_left.as_ref() {
+        let seconds = timer.remaining_secs().ceil() as u32;
+        lines.push(locale.format("hud-time", &[("seconds", seconds.into())]));
+    }
+    if let Some(undos_left) = session.undos_left.filter(|undos_left| *undos_left > 0) {
+        lines.push(locale.format("hud-undo", &[("undos", undos_left.into())]));
+    }
+    if autoplay.enabled {
+        lines.push(locale.text("hud-autoplay"));
+    }
+
+    display.0 = lines.join("\n");
+}
+
+fn player_score_lines(score: &ScoreStorage, locale: &Locale) -> Vec<String> {
+    Player::ALL
+        .iter()
+        .map(|player| {
+            locale.format(
+                "hud-player-score",
+                &[
+                    ("player", locale.text(player.message_id()).into()),
+                    ("score", score.of(*player).into()),
+                ],
+            )
+        })
+        .collect()
+}
+
+fn goal_lines(level: &Level, collected: &Collected, locale: &Locale) -> Vec<String> {
+    level
+        .goals
+        .iter()
+        .map(|goal| match goal {
+            LevelGoal::Score(score) => locale.format("hud-target", &[("score", (*score).into())]),
+            LevelGoal::Collect { form, count } => locale.format(
+                "hud-collect",
+                &[
+                    ("form", form_name(locale, *form, *count).into()),
+                    ("collected", collected.of(*form).min(*count).into()),
+                    ("count", (*count).into()),
+                ],
+            ),
+        })
+        .collect()
+}
+
+fn versus_lines(versus: &Versus, score: &ScoreStorage, locale: &Locale) -> Vec<String> {
+    let mut lines = player_score_lines(score, locale);
+
+    if !versus.is_over() {
+        lines.push(locale.format(
+            "hud-turn",
+            &[
+                ("player", locale.text(versus.player().message_id()).into()),
+                ("round", versus.round().into()),
+                ("rounds", VERSUS_ROUNDS.into()),
+            ],
+        ));
+    }
+
+    lines
+}
+
+fn setup(
+    mut commands: Commands,
+    session: Res<GameSession>,
+    saved: Option<Res<SavedGame>>,
+    mut tick: ResMut<GameTick>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme: Res<ActiveTheme>,
+    settings: Res<Settings>,
+) {
+    let board_assets = BoardAssets::new(
+        &theme.0,
+        &settings.accessibility,
+        &mut meshes,
+        &mut materials,
+    );
+
+    commands.insert_resource(board_assets.clone());
+
+    let owners = session.mode.board_owners();
+    for owner in owners {
+        spawn_board(
+            &mut commands,
+            &board_assets,
+            *owner,
+            board_origin(*owner, owners.len()),
+            session.seed,
+            session.level.as_ref().map(|level| &level.layout),
+            saved.as_deref(),
+        );
+    }
+
+    if let Some(saved) = saved.as_ref() {
+        tick.0 = saved.tick;
+    }
+
+    commands.remove_resource::<SavedGame>();
+}
+
+/// Строит доску игрока: сущность с `Board` и её состоянием, фон клеток, заслонку и фишки.
+///
+/// Фишки становятся детьми доски, так системы находят доску фишки. Сама доска стоит в начале координат,
+/// а место доски в мире задаёт `origin`. Фишки уровня из `layout` стоят на своих местах, остальные случайные.
+/// Дыры из `layout` остаются без клетки, преграды без фишки.
+fn spawn_board(
+    commands: &mut Commands,
+    board_assets: &BoardAssets,
+    owner: Player,
+    origin: Vec2,
+    seed: u64,
+    layout: Option<&BoardLayout>,
+    saved: Option<&SavedGame>,
+) {
+    let mut board = Board::with_origin(origin);
+    let mut rng = match saved {
+        Some(saved) => saved.rng.clone(),
+        None => ChaCha8Rng::seed_from_u64(seed),
+    };
+    let board_entity = commands
+        .spawn((
+            Transform::default(),
+            Visibility::default(),
+            DespawnOnExit(AppState::InGame),
+        ))
+        .id();
+
+    let hidden_board_height = board.height() - board.visible_height();
+    let hidden_board_rectangle_mesh = board_assets.rectangle_mesh.clone();
+    let cell_mesh = board_assets.rectangle_mesh.clone();
+
+    let hidden_board_rectangle_pos = {
+        let bottom_left = board.bottom_left();
+        let top_right = board.top_right();
+
+        Vec3::new(
+            (bottom_left.x + top_right.x) / 2.,
+            hidden_board_height as f32 * 0.5 * board.cell_size()
+                + top_right.y
+                + board.cell_size() * 0.5,
+            100.,
+        )
+    };
+    commands.spawn((
+        Transform::from_translation(hidden_board_rectangle_pos).with_scale(Vec3::new(
+            board.cell_size() * board.width() as f32,
+            board.cell_size() * hidden_board_height as f32,
+            0.,
+        )),
+        Mesh2d(hidden_board_rectangle_mesh),
+        MeshMaterial2d(board_assets.background_material.clone()),
+        BoardCover,
+        DespawnOnExit(AppState::InGame),
+    ));
+
+    for i in 0..board.height() {
+        let mut row = Vec::with_capacity(board.width());
+        for j in 0..board.width() {
+            let kind = layout.map_or(CellKind::Open, |layout| layout.kind_at((i, j)));
+            let form = match saved {
+                _ if !kind.is_open() => None,
+                Some(saved) => saved.board[i][j],
+                None => Some(
+                    layout
+                        .and_then(|layout| layout.form_at((i, j)))
+                        .unwrap_or_else(|| rng.random()),
+                ),
+            };
+
+            let Vec2 { x, y } = board.get_cell_coord((i, j));
+            let cell_transform = Transform::from_xyz(x, y, 0.).with_scale(Vec3::new(
+                board.cell_size() - board.border_width(),
+                board.cell_size() - board.border_width(),
+                0.,
+            ));
+
+            match kind {
+                CellKind::Open => {
+                    commands.spawn((
+                        Mesh2d(cell_mesh.clone()),
+                        MeshMaterial2d(board_assets.cell_material.clone()),
+                        cell_transform,
+                        CellBackground,
+                        DespawnOnExit(AppState::InGame),
+                    ));
+                }
+                CellKind::Blocker => {
+                    commands.spawn((
+                        Mesh2d(cell_mesh.clone()),
+                        MeshMaterial2d(board_assets.blocker_material.clone()),
+                        cell_transform,
+                        DespawnOnExit(AppState::InGame),
+                    ));
+                }
+                CellKind::Hole => {}
+            }
+
+            row.push(Cell {
+                tile: form.map(|form| {
+                    spawn_tile(
+                        commands,
+                        board_assets,
+                        board_entity,
+                        &board,
+                        (i, j).into(),
+                        form,
+                    )
+                }),
+                kind,
+            });
+        }
+        board.push_row(row);
+    }
+
+    commands.entity(board_entity).insert((
+        board,
+        owner,
+        Selection::default(),
+        TilesToDespawn::default(),
+        GameRng(rng),
+    ));
+}
+
+#[derive(Component)]
+struct CellBackground;
+
+/// Заслонка над доской, за которой прячутся скрытые ряды.
+#[derive(Component)]
+struct BoardCover;
+
+/// Значок формы внутри фишки.
+#[derive(Component)]
+struct Glyph;
+
+/// Из настроек на вид доски влияют только палитра и значки, громкость или язык её не перекрашивают.
+fn board_style_changed(
+    settings: Res<Settings>,
+    mut style: Local<(accessibility::Palette, bool)>,
+) -> bool {
+    let current = (
+        settings.accessibility.palette,
+        settings.accessibility.glyphs,
+    );
+
+    current != std::mem::replace(&mut *style, current)
+}
+
+/// Перекрашивает доску в новую тему или палитру. Фишки остаются теми же сущностями, поэтому падение и обмены не прерываются.
+fn restyle_board(
+    mut commands: Commands,
+    theme: Res<ActiveTheme>,
+    settings: Res<Settings>,
+    boards: Query<&Board>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    cells: Query<Entity, With<CellBackground>>,
+    covers: Query<Entity, With<BoardCover>>,
+    glyphs: Query<&mut Visibility, With<Glyph>>,
+) {
+    let board_assets = BoardAssets::new(
+        &theme.0,
+        &settings.accessibility,
+        &mut meshes,
+        &mut materials,
+    );
+
+    for mut visibility in glyphs {
+        *visibility = board_assets.glyph_visibility;
+    }
+
+    for cell in cells {
+        commands
+            .entity(cell)
+            .insert(MeshMaterial2d(board_assets.cell_material.clone()));
+    }
+    for cover in covers {
+        commands
+            .entity(cover)
+            .insert(MeshMaterial2d(board_assets.background_material.clone()));
+    }
+    let tiles = boards
+        .iter()
+        .flat_map(|board| board.into_iter().flatten().filter_map(|cell| cell.tile));
+    for tile in tiles {
+        let (mesh, material) = board_assets.form(tile.form);
+
+        commands
+            .entity(tile.visual_entity)
+            .insert((Mesh2d(mesh), MeshMaterial2d(material)));
+        commands
+            .entity(tile.select_area_entity)
+            .insert(MeshMaterial2d(board_assets.select_area_material.clone()));
+    }
+
+    commands.insert_resource(board_assets);
+}
+
+/// Пересоздаёт сущности всех фишек по сохранённым формам. Дыры и преграды остаются пустыми.
+pub fn rebuild_tiles(
+    commands: &mut Commands,
+    board_entity: Entity,
+    board: &mut Board,
+    board_assets: &BoardAssets,
+    forms: &[Vec<Option<Form>>],
+) {
+    for row_id in 0..board.height() {
+        for col_id in 0..board.width() {
+            if let Some(tile) = board[row_id][col_id].tile.take() {
+                commands.entity(tile.entity).despawn();
+            }
+
+            let Some(form) = forms[row_id][col_id].filter(|_| board[row_id][col_id].kind.is_open())
+            else {
+                continue;
+            };
+            let tile = spawn_tile(
+                commands,
+                board_assets,
+                board_entity,
+                board,
+                (row_id, col_id).into(),
+                form,
+            );
+            board[row_id][col_id].tile = Some(tile);
+        }
+    }
+}
+
+fn spawn_tile(
+    commands: &mut Commands,
+    board_assets: &BoardAssets,
+    board_entity: Entity,
+    board: &Board,
+    idx: BoardIndex,
+    form: Form,
+) -> Tile {
+    let (form_mesh, form_material) = board_assets.form(form);
+    let Vec2 { x, y } = board.get_cell_coord(idx);
+
+    let select_area_entity = commands
+        .spawn((
+            Mesh2d(board_assets.rectangle_mesh.clone()),
+            MeshMaterial2d(board_assets.select_area_material.clone()),
+            Transform::from_xyz(0., 0., 1.),
+            SelectArea,
+            Visibility::Hidden,
+        ))
+        .id();
+    let visual_entity = commands
+        .spawn((
+            Mesh2d(form_mesh),
+            MeshMaterial2d(form_material),
+            Transform::from_xyz(0., 0., 100.).with_scale(Vec3::splat(0.95)),
+        ))
+        .with_child((
+            Mesh2d(board_assets.glyphs[form as usize].clone()),
+            MeshMaterial2d(board_assets.glyph_material.clone()),
+            Transform::from_xyz(0., 0., 1.),
+            board_assets.glyph_visibility,
+            Glyph,
+        ))
+        .id();
+
+    let tile_entity = commands
+        .spawn((
+            TileBundle {
+                transform: Transform::from_xyz(x, y, 0.5).with_scale(Vec3::new(
+                    board.cell_size() - board.border_width(),
+                    board.cell_size() - board.border_width(),
+                    0.,
+                )),
+                visibility: Visibility::Inherited,
+                despawn_on_exit: DespawnOnExit(AppState::InGame),
+            },
+            ChildOf(board_entity),
+        ))
+        .add_children(&[select_area_entity, visual_entity])
+        .id();
+
+    Tile {
+        form,
+        entity: tile_entity,
+        select_area_entity,
+        visual_entity,
+    }
+}
+
+fn handle_selection(
+    boards: Query<(&mut Board, &mut Selection)>,
+    mut session: ResMut<GameSession>,
+    tick: Res<GameTick>,
+    mut recorder: Option<ResMut<ReplayRecorder>>,
+    mut commands: Commands,
+    moving_tiles_qeury: Query<(), With<Moving>>,
+    mut swap_requested: MessageWriter<SwapRequested>,
+) {
+    for (mut board, mut selection) in boards {
+        while let Some(idx) = selection.to_unselect.pop() {
+            set_selected(&mut commands, &board[idx], false);
+        }
+
+        if selection
+            .selected
+            .and_then(|idx| board[idx].tile.as_ref())
+            .is_none_or(|tile| moving_tiles_qeury.get(tile.entity).is_ok())
+        {
+            selection.selected = None;
+            continue;
+        }
+
+        if let Some((last_selected, selected)) = selection.last_selected.zip(selection.selected) {
+            if last_selected == selected {
+                set_selected(&mut commands, &board[selected], false);
+                selection.last_selected = None;
+                selection.selected = None;
+                continue;
+            }
+            let di = (last_selected.row_id() as isize - selected.row_id() as isize).abs();
+            let dj = (last_selected.col_id() as isize - selected.col_id() as isize).abs();
+
+            if di + dj == 1
+                && !session
+                    .versus
+                    .is_some_and(|versus| versus.turn_in_progress())
+                && board[last_selected].tile.is_some()
+                && let Some(selected_tile) = board[selected].tile
+            {
+                set_selected(&mut commands, &board[selected], false);
+                selection.last_selected = None;
+                selection.selected = None;
+
+                if let Some(moves_left) = session.moves_left.as_mut() {
+                    *moves_left -= 1;
+                }
+                if let Some(versus) = session.versus.as_mut() {
+                    versus.take_turn();
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record_swap(tick.0, last_selected, selected);
+                }
+                swap_requested.write(SwapRequested {
+                    from: last_selected,
+                    to: selected,
+                });
+
+                swap_tiles(&mut board, &mut commands, last_selected, selected);
+                commands
+                    .entity(selected_tile.entity)
+                    .insert(CheckMatchesOrSwap([last_selected, selected]));
+            } else {
+                set_selected(&mut commands, &board[last_selected], false);
+                set_selected(&mut commands, &board[selected], true);
+            }
+
+            continue;
+        }
+
+        if let Some(cell) = selection.selected.map(|idx| &board[idx]) {
+            set_selected(&mut commands, cell, true);
+        }
+    }
+}
+
+fn set_selected(commands: &mut Commands, cell: &Cell, selected: bool) {
+    let (new_visibility, new_scale) = if selected {
+        (
+            Visibility::Inherited,
+            Vec3::new(cell.size() + 10., cell.size() + 10., 0.),
+        )
+    } else {
+        (
+            Visibility::Hidden,
+            Vec3::new(cell.tile_size(), cell.tile_size(), 0.),
+        )
+    };
+
+    if let Some(tile) = cell.tile.as_ref() {
+        commands
+            .entity(tile.entity)
+            .entry::<Transform>()
+            .and_modify(move |mut transform| transform.scale = new_scale);
+
+        commands
+            .entity(tile.select_area_entity)
+            .entry::<Visibility>()
+            .and_modify(move |mut visibility| *visibility = new_visibility);
+    }
+}
+
+/// Мышью играют на доске первого игрока.
+fn handle_click(
+    buttons: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window>,
+    boards: Query<(&Board, &mut Selection, &Player)>,
+    session: Res<GameSession>,
+    camera_query: Single<(&Camera, &GlobalTransform)>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) || session.is_over() {
+        return;
+    }
+    let Some((board, mut selection, _)) = boards
+        .into_iter()
+        .find(|(_, _, owner)| **owner == Player::One)
+    else {
+        return;
+    };
+    let Some(mouse_pos) = window.cursor_position() else {
+        return;
+    };
+
+    println!("Clicked on ({}, {})", mouse_pos.x, mouse_pos.y);
+
+    let (camera, camera_transform) = *camera_query;
+    let Ok(mouse_world_pos) = camera.viewport_to_world_2d(camera_transform, mouse_pos) else {
+        return;
+    };
+
+    println!(
+        "Mouse in world on {}, {}",
+        mouse_world_pos.x, mouse_world_pos.y
+    );
+
+    let bottom_left_border_pos = board.bottom_left() - board.cell_size() / 2.;
+    let upper_right_border_pos = board.top_right() + board.cell_size() / 2.;
+
+    // println!("Bottom left border: {bottom_left_border_pos}");
+    // println!("Upper right border: {upper_right_border_pos}");
+
+    if !(mouse_world_pos.x > bottom_left_border_pos.x
+        && mouse_world_pos.x < upper_right_border_pos.x
+        && mouse_world_pos.y > bottom_left_border_pos.y
+        && mouse_world_pos.y < upper_right_border_pos.y)
+    {
+        return;
+    }
+
+    let clicked_cell_pos = ((mouse_world_pos - bottom_left_border_pos) / board.cell_size()).floor();
+
+    // println!("Clicked sell pos: {clicked_cell_pos}");
+
+    let j = clicked_cell_pos.x as usize;
+    let i = clicked_cell_pos.y as usize;
+
+    println!("Defined cell: {}, {}", i, j);
+
+    selection.select((i, j).into());
+}
+
+fn check_swapped_for_matching(
+    mut commands: Commands,
+    mut boards: Query<&mut Board>,
+    mut session: ResMut<GameSession>,
+    mut undo_history: ResMut<UndoHistory>,
+    swapped_tiles: Query<(Entity, &CheckMatchesOrSwap, &ChildOf), Without<Moving>>,
+    moving_tiles: Query<(), With<Moving>>,
+    mut swap_rejected: MessageWriter<SwapRejected>,
+) {
+    for (entity, swapped, child_of) in swapped_tiles {
+        let Ok(mut board) = boards.get_mut(child_of.parent()) else {
+            continue;
+        };
+        let has_matches = !resting_grid(&board, &moving_tiles)
+            .runs_through(&swapped.0)
+            .is_empty();
+
+        commands.entity(entity).remove::<CheckMatchesOrSwap>();
+
+        if has_matches {
+            session.moves_made += 1;
+            undo_history.push_settled();
+        } else {
+            let from = swapped.0[0];
+            let to = swapped.0[1];
+
+            swap_tiles(&mut board, &mut commands, from, to);
+            swap_rejected.write(SwapRejected { from, to });
+
+            // Ход без совпадений не засчитывается.
+            if let Some(moves_left) = session.moves_left.as_mut() {
+                *moves_left += 1;
+            }
+            if let Some(versus) = session.versus.as_mut() {
+                versus.cancel_turn();
+            }
+        }
+    }
+}
+
+fn swap_tiles(board: &mut Board, commands: &mut Commands, idx1: BoardIndex, idx2: BoardIndex) {
+    let Some(tile1) = board[idx1].tile.as_ref() else {
+        return;
+    };
+    let Some(tile2) = board[idx2].tile.as_ref() else {
+        return;
+    };
+
+    commands.entity(tile1.entity).insert(Moving {
+        from: idx1,
+        to: idx2,
+    });
+    commands.entity(tile2.entity).insert(Moving {
+        from: idx2,
+        to: idx1,
+    });
+
+    let tmp = board[idx1].tile;
+    board[idx1].tile = board[idx2].tile;
+    board[idx2].tile = tmp;
+}
+
+fn check_board_for_matching(
+    mut score: ResMut<ScoreStorage>,
+    mut session: ResMut<GameSession>,
+    boards: Query<(Entity, &Board, &mut TilesToDespawn, &Player)>,
+    moving_tiles: Query<(), With<Moving>>,
+    mut match_found: MessageWriter<MatchFound>,
+) {
+    for (board_entity, board, mut tiles_to_despawn, owner) in boards {
+        let runs = resting_grid(board, &moving_tiles).find_runs();
+        let matches = group_runs(&runs);
+
+        for found in &matches {
+            session.collected.add(found.form, found.cells.len());
+        }
+        match_found.write_batch(matches.into_iter().map(|found| MatchFound {
+            board: board_entity,
+            form: found.form,
+            cells: found.cells,
+            shape: found.shape,
+        }));
+
+        let scorer = session.scorer(*owner);
+        for run in runs {
+            score.add(scorer, SCORE_PER_TILE * run.len());
+            tiles_to_despawn.0.extend(run.cells);
+        }
+
+        if !session.target_reached
+            && (session
+                .target_score
+                .is_some_and(|target_score| score.of(scorer) >= target_score)
+                || session
+                    .level
+                    .as_ref()
+                    .is_some_and(|level| level.goals_met(score.of(scorer), &session.collected)))
+        {
+            session.target_reached = true;
+            session.first_to_target = Some(scorer);
+        }
+    }
+}
+
+/// Формы на доске без фишек, которые ещё в движении: пока фишка не встала на место, в ряды она не входит.
+fn resting_grid(board: &Board, moving_tiles: &Query<(), With<Moving>>) -> Grid {
+    let mut grid = Grid::from(board);
+
+    for (row_id, row) in board.into_iter().enumerate() {
+        for (col_id, cell) in row.iter().enumerate() {
+            if cell
+                .tile
+                .is_some_and(|tile| moving_tiles.contains(tile.entity))
+            {
+                grid.set((row_id, col_id).into(), None);
+            }
+        }
+    }
+
+    grid
+}
+
+fn despawn_tiles(
+    boards: Query<(&mut Board, &mut TilesToDespawn)>,
+    mut commands: Commands,
+    mut tiles_cleared: MessageWriter<TilesCleared>,
+    mut tiles_fell: MessageWriter<TilesFell>,
+) {
+    for (mut board, mut tiles_to_despawn) in boards {
+        if tiles_to_despawn.0.is_empty() {
+            continue;
+        }
+
+        let mut cleared = Vec::new();
+        while let Some(index) = tiles_to_despawn.0.pop() {
+            if let Some(tile) = board[index].tile.take() {
+                commands.entity(tile.entity).despawn();
+                cleared.push(index);
+            }
+        }
+        tiles_cleared.write(TilesCleared { cells: cleared });
+
+        let mut falls = Vec::new();
+
+        for col_id in 0..board.width() {
+            // Дыры и преграды не двигаются, фишки падают сквозь них в следующую обычную клетку.
+            let open_rows: Vec<_> = (0..board.height())
+                .filter(|row_id| board[*row_id][col_id].kind.is_open())
+                .collect();
+            let first_empty = open_rows.iter().position(|row_id| {
+                *row_id < board.visible_height() && board[*row_id][col_id].tile.is_none()
+            });
+
+            if let Some(first_empty) = first_empty {
+                let mut targets = open_rows[first_empty..].iter();
+                for row_id in &open_rows[first_empty..] {
+                    if let Some(tile) = board[*row_id][col_id].tile.take() {
+                        let target = *targets.next().expect("Column has more tiles than cells");
+                        let from = (*row_id, col_id).into();
+                        let to = (target, col_id).into();
+                        commands.entity(tile.entity).insert(Moving { from, to });
+                        falls.push((from, to));
+
+                        board[target][col_id].tile = Some(tile);
+                    }
+                }
+            }
+        }
+
+        tiles_fell.write(TilesFell { falls });
+    }
+}
+
+fn spawn_tiles(
+    boards: Query<(Entity, &mut Board, &mut GameRng)>,
+    mut commands: Commands,
+    board_assets: Res<BoardAssets>,
+) {
+    for (board_entity, mut board, mut rng) in boards {
+        for col_id in 0..board.width() {
+            for row_id in board.visible_height()..board.height() {
+                // Форма выбирается только для пустых клеток, иначе последовательность ГПСЧ зависит от частоты кадров.
+                if board[row_id][col_id].tile.is_some() || !board[row_id][col_id].kind.is_open() {
+                    continue;
+                }
+
+                let form = rng.0.random();
+                let tile = spawn_tile(
+                    &mut commands,
+                    &board_assets,
+                    board_entity,
+                    &board,
+                    (row_id, col_id).into(),
+                    form,
+                );
+
+                board[row_id][col_id].tile = Some(tile);
+            }
+        }
+    }
+}
+
+fn move_tiles(
+    time: Res<Time>,
+    mut commands: Commands,
+    boards: Query<&Board>,
+    session: Res<GameSession>,
+    query: Query<(Entity, &mut Transform, &Moving, &ChildOf)>,
+) {
+    for (entity, mut transform, moving, child_of) in query {
+        let Ok(board) = boards.get(child_of.parent()) else {
+            continue;
+        };
+        let target_coord = board.get_cell_coord(moving.to);
+
+        let dx = (moving.to.col_id() as isize - moving.from.col_id() as isize).signum() as f32;
+        let dy = (moving.to.row_id() as isize - moving.from.row_id() as isize).signum() as f32;
+        let direction = Vec2::new(dx, dy);
+
+        let delta = TILE_VELOCITY * session.animation_speed * time.delta_secs();
+        transform.translation += direction.extend(0.) * delta;
+        if target_coord.abs_diff_eq(transform.translation.xy(), delta) {
+            transform.translation.x = target_coord.x;
+            transform.translation.y = target_coord.y;
+            commands.entity(entity).remove::<Moving>();
+        }
+    }
+}
+
+/// Выбранные фишки на доске.
+#[derive(Component, Default)]
+pub struct Selection {
+    to_unselect: Vec<BoardIndex>,
+    last_selected: Option<BoardIndex>,
+    selected: Option<BoardIndex>,
+}
+
+impl Selection {
+    /// Выбирает клетку, как клик по ней.
+    pub fn select(&mut self, idx: BoardIndex) {
+        let last_selected = self.selected;
+
+        self.to_unselect.extend(last_selected);
+        self.last_selected = last_selected;
+        self.selected = Some(idx);
+    }
+
+    /// Выбирает две фишки, как два клика подряд: `handle_selection` сам проверит и выполнит обмен.
+    pub fn request_swap(&mut self, from: BoardIndex, to: BoardIndex) {
+        self.to_unselect.extend(self.selected);
+        self.last_selected = Some(from);
+        self.selected = Some(to);
+    }
+}
+
+#[derive(Component, Default)]
+struct TilesToDespawn(Vec<BoardIndex>);
+
+#[derive(Component)]
+struct SelectArea;
+
+#[derive(Bundle)]
+struct TileBundle {
+    transform: Transform,
+    visibility: Visibility,
+    despawn_on_exit: DespawnOnExit<AppState>,
+}
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct Moving {
+    from: BoardIndex,
+    to: BoardIndex,
+}
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct CheckMatchesOrSwap([BoardIndex; 2]);
+
+#[derive(Resource, Clone)]
+pub struct BoardAssets {
+    rectangle_mesh: Handle<Mesh>,
+    select_area_material: Handle<ColorMaterial>,
+    cell_material: Handle<ColorMaterial>,
+    background_material: Handle<ColorMaterial>,
+    blocker_material: Handle<ColorMaterial>,
+    /// Меш и материал каждой формы в порядке `Form::ALL`.
+    forms: Vec<(Handle<Mesh>, Handle<ColorMaterial>)>,
+    glyphs: Vec<Handle<Mesh>>,
+    glyph_material: Handle<ColorMaterial>,
+    glyph_visibility: Visibility,
+}
+
+impl BoardAssets {
+    fn form(&self, form: Form) -> (Handle<Mesh>, Handle<ColorMaterial>) {
+        self.forms[form as usize].clone()
+    }
+
+    fn new(
+        theme: &Theme,
+        accessibility: &AccessibilitySettings,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Self {
+        let forms = Form::ALL
+            .iter()
+            .map(|form| {
+                let style = theme.forms.get(*form);
+                let color = accessibility.palette.color(*form).unwrap_or(style.color.0);
+
+                match &style.look {
+                    FormLook::Shape(shape) => (meshes.add(shape.mesh()), materials.add(color)),
+                    FormLook::Sprite(_) => (
+                        meshes.add(Rectangle::from_size(Vec2::splat(0.9))),
+                        materials.add(ColorMaterial {
+                            color,
+                            texture: style.image.clone(),
+                            ..Default::default()
+                        }),
+                    ),
+                }
+            })
+            .collect();
+
+        Self {
+            rectangle_mesh: meshes.add(Rectangle::default()),
+            // @FIXME Вернуть альфа канал 0.75
+            // С параметром альфа канала выглядит как будто область выделения находится над фигурой.
+            select_area_material: materials.add(theme.selection.0),
+            cell_material: materials.add(theme.cell.0),
+            background_material: materials.add(theme.background.0),
+            blocker_material: materials.add(BLOCKER_COLOR),
+            forms,
+            glyphs: Form::ALL
+                .iter()
+                .map(|form| meshes.add(glyph_mesh(*form)))
+                .collect(),
+            glyph_material: materials.add(GLYPH_COLOR),
+            glyph_visibility: if accessibility.glyphs {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+        }
+    }
+}
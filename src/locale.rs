@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use fluent_bundle::{
+    FluentArgs, FluentResource, FluentValue, concurrent::FluentBundle, memoizer::MemoizerKind,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// Меньшие числа в русском не разбивают на разряды: 1000, но 10 000.
+const RUSSIAN_MIN_GROUPED: f64 = 10_000.;
+
+/// Переводы интерфейса из `assets/locales`. Файлы вшиты в игру, чтобы текст был и до загрузки ассетов.
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>().add_systems(
+            Update,
+            (
+                apply_language.run_if(resource_changed::<Settings>),
+                translate_texts,
+                set_window_title.run_if(resource_changed::<Locale>),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Russian];
+
+    /// Название на самом языке, чтобы его узнал тот, кто на нём читает.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Russian => "Русский",
+        }
+    }
+
+    /// Шрифт для языков, букв которых нет во встроенном шрифте Bevy. Шрифт темы важнее.
+    pub fn font(&self) -> Option<&'static str> {
+        match self {
+            Language::English => None,
+            Language::Russian => Some("fonts/DejaVuSans-Bold.ttf"),
+        }
+    }
+
+    pub fn next(&self) -> Language {
+        match self {
+            Language::English => Language::Russian,
+            Language::Russian => Language::English,
+        }
+    }
+
+    fn bundle(&self) -> FluentBundle<FluentResource> {
+        let (id, source) = match self {
+            Language::English => ("en", include_str!("../assets/locales/en.ftl")),
+            Language::Russian => ("ru", include_str!("../assets/locales/ru.ftl")),
+        };
+
+        let resource = FluentResource::try_new(source.to_string())
+            .unwrap_or_else(|(_, errors)| panic!("Broken {id}.ftl: {errors:?}"));
+        let mut bundle = FluentBundle::new_concurrent(vec![id.parse().unwrap()]);
+        bundle
+            .add_resource(resource)
+            .unwrap_or_else(|errors| panic!("Broken {id}.ftl: {errors:?}"));
+        // Метки направления текста Fluent вставляет для смеси письменностей, шрифту они не нужны.
+        bundle.set_use_isolating(false);
+        bundle.set_formatter(Some(match self {
+            Language::English => format_en,
+            Language::Russian => format_ru,
+        }));
+
+        bundle
+    }
+}
+
+/// Текущий язык и переводы. Чего нет в выбранном языке, берётся из английского.
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new(Language::default())
+    }
+}
+
+impl Locale {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            bundles: Language::ALL.iter().map(Language::bundle).collect(),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    /// Переводит сообщение с аргументами. Неизвестное ни одному языку сообщение выводится своим id.
+    pub fn format(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let args = args.iter().cloned().collect::<FluentArgs>();
+        let bundles =
+            [self.language, Language::English].map(|language| &self.bundles[language as usize]);
+
+        for bundle in bundles {
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(&args), &mut errors);
+            if !errors.is_empty() {
+                warn!("Failed to format {id:?}: {errors:?}");
+            }
+            return text.into_owned();
+        }
+
+        warn!("Missing translation for {id:?}");
+        id.to_string()
+    }
+}
+
+fn format_en<M: MemoizerKind>(value: &FluentValue, _: &M) -> Option<String> {
+    format_number(value, ",", 1000.)
+}
+
+fn format_ru<M: MemoizerKind>(value: &FluentValue, _: &M) -> Option<String> {
+    format_number(value, "\u{a0}", RUSSIAN_MIN_GROUPED)
+}
+
+/// Разбивает целые числа на разряды. Дробные Fluent выводит сам.
+fn format_number(value: &FluentValue, separator: &str, min_grouped: f64) -> Option<String> {
+    let FluentValue::Number(number) = value else {
+        return None;
+    };
+    if number.value.fract() != 0. || number.value.abs() < min_grouped {
+        return None;
+    }
+
+    let digits = (number.value.abs() as u64).to_string();
+    let groups = digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).unwrap())
+        .collect::<Vec<_>>();
+    let sign = if number.value < 0. { "-" } else { "" };
+
+    Some(format!("{sign}{}", groups.join(separator)))
+}
+
+/// Язык берётся из настроек. Тот же язык не трогает `Locale`, чтобы надписи не переводились зря.
+fn apply_language(settings: Res<Settings>, mut locale: ResMut<Locale>) {
+    if locale.language() != settings.language {
+        locale.set_language(settings.language);
+    }
+}
+
+/// Надпись, которую переводят по id и переводят заново при смене языка.
+#[derive(Component)]
+pub(crate) struct Localized(pub &'static str);
+
+fn translate_texts(locale: Res<Locale>, texts: Query<(Ref<Localized>, &mut Text)>) {
+    for (localized, mut text) in texts {
+        if locale.is_changed() || localized.is_added() {
+            text.0 = locale.text(localized.0);
+        }
+    }
+}
+
+fn set_window_title(locale: Res<Locale>, windows: Query<&mut Window>) {
+    for mut window in windows {
+        window.title = locale.text("game-title");
+    }
+}
@@ -0,0 +1,617 @@
+use bevy::prelude::*;
+use fluent_bundle::FluentValue;
+
+use crate::{
+    AppState, GameMode, GameOverState, GameSession, GameState, ScoreStorage,
+    audio::VolumeChannel,
+    daily::{DailyRecords, today},
+    editor::LevelEditor,
+    locale::{Locale, Localized},
+    race::race_lines,
+    replay::{Replay, watch_last_replay},
+    restart,
+    savegame::{ContinueSlot, continue_game},
+    settings::Settings,
+    storage::StorageRoot,
+    theme::{ActiveTheme, next_theme},
+    versus_lines,
+};
+
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.95);
+const TITLE_COLOR: Color = Color::srgb(0.5, 0.5, 1.0);
+pub(crate) const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.22);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.35);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.45, 0.6);
+pub(crate) const OVERLAY_COLOR: Color = Color::srgba(0.05, 0.05, 0.08, 0.85);
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<MenuState>()
+            .add_systems(OnEnter(MenuState::Main), spawn_main_menu)
+            .add_systems(OnEnter(MenuState::Settings), spawn_settings_menu)
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(OnEnter(GameOverState::Summary), spawn_game_over_menu)
+            .add_systems(
+                Update,
+                (
+                    button_colors,
+                    handle_menu_actions,
+                    (
+                        display_window,
+                        display_volumes,
+                        display_effects,
+                        display_theme,
+                        display_gameplay,
+                        display_accessibility,
+                        display_language,
+                    )
+                        .run_if(in_state(MenuState::Settings)),
+                ),
+            );
+    }
+}
+
+#[derive(SubStates, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[source(AppState = AppState::MainMenu)]
+pub(crate) enum MenuState {
+    #[default]
+    Main,
+    Settings,
+    HighScores,
+    Campaign,
+}
+
+#[derive(Component, Clone, Copy)]
+pub(crate) enum MenuAction {
+    Continue,
+    Play(GameMode),
+    Campaign,
+    WatchReplay,
+    Editor,
+    Settings,
+    HighScores,
+    Quit,
+    Back,
+    ToggleFullscreen,
+    ToggleVsync,
+    Volume(VolumeChannel),
+    ToggleEffects,
+    NextTheme,
+    NextAnimationSpeed,
+    NextHintDelay,
+    NextPalette,
+    ToggleGlyphs,
+    ToggleScreenReader,
+    ToggleFormCues,
+    NextLanguage,
+    Resume,
+    Restart,
+    MainMenu,
+}
+
+#[derive(Component, Clone, Copy)]
+enum WindowLabel {
+    Fullscreen,
+    Vsync,
+}
+
+#[derive(Component)]
+struct VolumeLabel(VolumeChannel);
+
+#[derive(Component)]
+struct EffectsLabel;
+
+#[derive(Component)]
+struct ThemeLabel;
+
+#[derive(Component)]
+struct LanguageLabel;
+
+#[derive(Component, Clone, Copy)]
+enum GameplayLabel {
+    AnimationSpeed,
+    HintDelay,
+}
+
+#[derive(Component, Clone, Copy)]
+enum AccessibilityLabel {
+    Palette,
+    Glyphs,
+    ScreenReader,
+    FormCues,
+}
+
+pub(crate) fn screen_root(background: Color) -> impl Bundle {
+    (
+        Node {
+            width: percent(100),
+            height: percent(100),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: px(12),
+            ..Default::default()
+        },
+        BackgroundColor(background),
+    )
+}
+
+pub(crate) fn title(id: &'static str) -> impl Bundle {
+    (
+        Text::default(),
+        Localized(id),
+        TextFont {
+            font_size: 48.,
+            ..Default::default()
+        },
+        TextColor(TITLE_COLOR),
+        Node {
+            margin: UiRect::bottom(px(24)),
+            ..Default::default()
+        },
+    )
+}
+
+pub(crate) fn label(text: impl Into<String>) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: 25.,
+            ..Default::default()
+        },
+        TextColor(TEXT_COLOR),
+    )
+}
+
+pub(crate) fn localized_label(id: &'static str) -> impl Bundle {
+    (label(""), Localized(id))
+}
+
+pub(crate) fn menu_button(id: &'static str, action: MenuAction) -> impl Bundle {
+    (button(id), action)
+}
+
+pub(crate) fn button(id: &'static str) -> impl Bundle {
+    (button_node(), children![localized_label(id)])
+}
+
+/// Кнопка настройки: надпись с текущим значением каждый кадр обновляет своя система.
+fn setting_button(action: MenuAction) -> impl Bundle {
+    (button_node(), action, children![label("")])
+}
+
+fn button_node() -> impl Bundle {
+    (
+        Button,
+        Node {
+            width: px(280),
+            height: px(54),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        BackgroundColor(NORMAL_BUTTON),
+    )
+}
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    continue_slot: Res<ContinueSlot>,
+    root: Res<StorageRoot>,
+) {
+    commands
+        .spawn((screen_root(Color::NONE), DespawnOnExit(MenuState::Main)))
+        .with_children(|parent| {
+            parent.spawn(title("game-title"));
+
+            if continue_slot.0.is_some() {
+                parent.spawn(menu_button("menu-continue", MenuAction::Continue));
+            }
+
+            parent.spawn(menu_button("menu-campaign", MenuAction::Campaign));
+            for mode in GameMode::ALL {
+                parent.spawn(menu_button(mode.message_id(), MenuAction::Play(mode)));
+            }
+
+            if Replay::last_path(&root).is_some() {
+                parent.spawn(menu_button("menu-watch-replay", MenuAction::WatchReplay));
+            }
+
+            parent.spawn(menu_button("menu-editor", MenuAction::Editor));
+            parent.spawn(menu_button("menu-settings", MenuAction::Settings));
+            parent.spawn(menu_button("menu-high-scores", MenuAction::HighScores));
+            parent.spawn(menu_button("menu-quit", MenuAction::Quit));
+        });
+}
+
+/// Настроек больше, чем влезает в столбец, поэтому кнопки идут в две колонки.
+fn spawn_settings_menu(mut commands: Commands) {
+    commands
+        .spawn((screen_root(Color::NONE), DespawnOnExit(MenuState::Settings)))
+        .with_children(|parent| {
+            parent.spawn(title("menu-settings"));
+            parent
+                .spawn(Node {
+                    width: px(580),
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    column_gap: px(12),
+                    row_gap: px(12),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for (action, label) in [
+                        (MenuAction::ToggleFullscreen, WindowLabel::Fullscreen),
+                        (MenuAction::ToggleVsync, WindowLabel::Vsync),
+                    ] {
+                        parent.spawn((setting_button(action), label));
+                    }
+
+                    for channel in VolumeChannel::ALL {
+                        parent.spawn((
+                            setting_button(MenuAction::Volume(channel)),
+                            VolumeLabel(channel),
+                        ));
+                    }
+
+                    parent.spawn((setting_button(MenuAction::ToggleEffects), EffectsLabel));
+                    parent.spawn((setting_button(MenuAction::NextTheme), ThemeLabel));
+                    for (action, label) in [
+                        (
+                            MenuAction::NextAnimationSpeed,
+                            GameplayLabel::AnimationSpeed,
+                        ),
+                        (MenuAction::NextHintDelay, GameplayLabel::HintDelay),
+                    ] {
+                        parent.spawn((setting_button(action), label));
+                    }
+                    for (action, label) in [
+                        (MenuAction::NextPalette, AccessibilityLabel::Palette),
+                        (MenuAction::ToggleGlyphs, AccessibilityLabel::Glyphs),
+                        (
+                            MenuAction::ToggleScreenReader,
+                            AccessibilityLabel::ScreenReader,
+                        ),
+                        (MenuAction::ToggleFormCues, AccessibilityLabel::FormCues),
+                    ] {
+                        parent.spawn((setting_button(action), label));
+                    }
+                    parent.spawn((setting_button(MenuAction::NextLanguage), LanguageLabel));
+                });
+
+            parent.spawn(menu_button("menu-back", MenuAction::Back));
+        });
+}
+
+fn spawn_pause_menu(mut commands: Commands, editor: Option<Res<LevelEditor>>) {
+    commands
+        .spawn((screen_root(OVERLAY_COLOR), DespawnOnExit(GameState::Paused)))
+        .with_children(|parent| {
+            parent.spawn(title("pause-title"));
+            parent.spawn(menu_button("pause-resume", MenuAction::Resume));
+            parent.spawn(menu_button("pause-restart", MenuAction::Restart));
+            if editor.is_some() {
+                parent.spawn(menu_button("editor-return", MenuAction::Editor));
+            }
+            parent.spawn(menu_button("pause-main-menu", MenuAction::MainMenu));
+        });
+}
+
+fn spawn_game_over_menu(
+    mut commands: Commands,
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    locale: Res<Locale>,
+    editor: Option<Res<LevelEditor>>,
+    daily_records: Res<DailyRecords>,
+) {
+    let lines = if session.mode.is_duel() {
+        // В гонке побеждает тот, кто первым набрал цель, даже если каскад соперника потом его обогнал.
+        let result = match session.first_to_target().or_else(|| score.leader()) {
+            Some(player) => locale.format(
+                "versus-winner",
+                &[("player", locale.text(player.message_id()).into())],
+            ),
+            None => locale.text("versus-draw"),
+        };
+        let mut lines = match session.versus() {
+            Some(versus) => versus_lines(versus, &score, &locale),
+            None => race_lines(&score, &locale),
+        };
+        lines.push(result);
+        lines
+    } else {
+        let mut lines = vec![locale.format("score", &[("score", score.total().into())])];
+        if let Some(best) = session.day().and_then(|day| daily_records.best(day)) {
+            lines.push(locale.format("daily-best", &[("score", best.into())]));
+            lines.push(locale.format(
+                "daily-streak",
+                &[("days", daily_records.streak(today()).into())],
+            ));
+        }
+        if let Some(level) = session
+            .level()
+            .filter(|_| session.mode == GameMode::Campaign)
+        {
+            lines.push(if session.level_cleared() {
+                locale.format(
+                    "campaign-stars",
+                    &[
+                        ("stars", level.stars(score.total()).into()),
+                        ("max", level.max_stars().into()),
+                    ],
+                )
+            } else {
+                locale.text("campaign-failed")
+            });
+        }
+        lines
+    };
+
+    commands
+        .spawn((
+            screen_root(OVERLAY_COLOR),
+            DespawnOnExit(GameOverState::Summary),
+        ))
+        .with_children(|parent| {
+            parent.spawn(title("game-over-title"));
+            for line in lines {
+                parent.spawn(label(line));
+            }
+            parent.spawn(menu_button("pause-restart", MenuAction::Restart));
+            if editor.is_some() {
+                parent.spawn(menu_button("editor-return", MenuAction::Editor));
+            }
+            parent.spawn(menu_button("pause-main-menu", MenuAction::MainMenu));
+        });
+}
+
+fn button_colors(
+    buttons: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, mut background) in buttons {
+        background.0 = match interaction {
+            Interaction::Pressed => PRESSED_BUTTON,
+            Interaction::Hovered => HOVERED_BUTTON,
+            Interaction::None => NORMAL_BUTTON,
+        };
+    }
+}
+
+fn handle_menu_actions(
+    mut commands: Commands,
+    actions: Query<(&Interaction, &MenuAction), (Changed<Interaction>, With<Button>)>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut settings: ResMut<Settings>,
+    root: Res<StorageRoot>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    for (interaction, action) in actions {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *action {
+            MenuAction::Continue => commands.run_system_cached(continue_game),
+            MenuAction::Play(mode) => {
+                commands.insert_resource(GameSession::new(mode, settings.animation_speed));
+                next_app_state.set(AppState::InGame);
+            }
+            MenuAction::Campaign => next_menu_state.set(MenuState::Campaign),
+            MenuAction::WatchReplay => commands.run_system_cached(watch_last_replay),
+            MenuAction::Editor => {
+                // После пробной игры ресурс уже есть, и редактор продолжает с той же правки.
+                commands.init_resource::<LevelEditor>();
+                next_app_state.set(AppState::Editor);
+            }
+            MenuAction::Settings => next_menu_state.set(MenuState::Settings),
+            MenuAction::HighScores => next_menu_state.set(MenuState::HighScores),
+            MenuAction::Quit => {
+                app_exit.write(AppExit::Success);
+            }
+            MenuAction::Back => next_menu_state.set(MenuState::Main),
+            MenuAction::ToggleFullscreen => {
+                settings.fullscreen = !settings.fullscreen;
+                save_settings(&settings, &root);
+            }
+            MenuAction::ToggleVsync => {
+                settings.vsync = !settings.vsync;
+                save_settings(&settings, &root);
+            }
+            MenuAction::Volume(channel) => {
+                settings.audio.step(channel);
+                save_settings(&settings, &root);
+            }
+            MenuAction::ToggleEffects => {
+                settings.effects = !settings.effects;
+                save_settings(&settings, &root);
+            }
+            MenuAction::NextTheme => commands.run_system_cached(next_theme),
+            MenuAction::NextAnimationSpeed => {
+                settings.next_animation_speed();
+                save_settings(&settings, &root);
+            }
+            MenuAction::NextHintDelay => {
+                settings.next_hint_delay();
+                save_settings(&settings, &root);
+            }
+            MenuAction::NextPalette => {
+                settings.accessibility.palette = settings.accessibility.palette.next();
+                save_settings(&settings, &root);
+            }
+            MenuAction::ToggleGlyphs => {
+                settings.accessibility.glyphs = !settings.accessibility.glyphs;
+                save_settings(&settings, &root);
+            }
+            MenuAction::ToggleScreenReader => {
+                settings.accessibility.screen_reader = !settings.accessibility.screen_reader;
+                save_settings(&settings, &root);
+            }
+            MenuAction::ToggleFormCues => {
+                settings.accessibility.form_cues = !settings.accessibility.form_cues;
+                save_settings(&settings, &root);
+            }
+            MenuAction::NextLanguage => {
+                settings.language = settings.language.next();
+                save_settings(&settings, &root);
+            }
+            MenuAction::Resume => next_game_state.set(GameState::Playing),
+            MenuAction::Restart => commands.run_system_cached(restart),
+            MenuAction::MainMenu => {
+                commands.remove_resource::<LevelEditor>();
+                next_app_state.set(AppState::MainMenu);
+            }
+        }
+    }
+}
+
+fn save_settings(settings: &Settings, root: &StorageRoot) {
+    if let Err(err) = settings.save(root) {
+        warn!("Failed to save settings: {err}");
+    }
+}
+
+fn on_off(locale: &Locale, enabled: bool) -> FluentValue<'static> {
+    locale.text(if enabled { "on" } else { "off" }).into()
+}
+
+fn display_window(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    labels: Query<(&WindowLabel, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (label, children) in labels {
+        let (id, enabled) = match label {
+            WindowLabel::Fullscreen => ("settings-fullscreen", settings.fullscreen),
+            WindowLabel::Vsync => ("settings-vsync", settings.vsync),
+        };
+        set_label(
+            children,
+            &mut texts,
+            &locale.format(id, &[("value", on_off(&locale, enabled))]),
+        );
+    }
+}
+
+fn display_volumes(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    labels: Query<(&VolumeLabel, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (VolumeLabel(channel), children) in labels {
+        let percent = (settings.audio.get(*channel) * 100.).round() as u32;
+        let value = locale.format(channel.message_id(), &[("percent", percent.into())]);
+        set_label(children, &mut texts, &value);
+    }
+}
+
+fn display_effects(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    labels: Query<&Children, With<EffectsLabel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let value = locale.format(
+        "settings-effects",
+        &[("value", on_off(&locale, settings.effects))],
+    );
+
+    for children in labels {
+        set_label(children, &mut texts, &value);
+    }
+}
+
+fn display_theme(
+    theme: Res<ActiveTheme>,
+    locale: Res<Locale>,
+    labels: Query<&Children, With<ThemeLabel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let value = locale.format("settings-theme", &[("value", theme.0.name.as_str().into())]);
+
+    for children in labels {
+        set_label(children, &mut texts, &value);
+    }
+}
+
+fn display_gameplay(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    labels: Query<(&GameplayLabel, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (label, children) in labels {
+        let value = match label {
+            GameplayLabel::AnimationSpeed => {
+                let percent = (settings.animation_speed * 100.).round() as u32;
+                locale.format("settings-animation-speed", &[("percent", percent.into())])
+            }
+            GameplayLabel::HintDelay => locale.format(
+                "settings-hint-delay",
+                &[("seconds", settings.hint_delay_secs.into())],
+            ),
+        };
+        set_label(children, &mut texts, &value);
+    }
+}
+
+fn display_accessibility(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    labels: Query<(&AccessibilityLabel, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let settings = &settings.accessibility;
+
+    for (label, children) in labels {
+        let (id, value) = match label {
+            AccessibilityLabel::Palette => (
+                "settings-palette",
+                locale.text(settings.palette.message_id()).into(),
+            ),
+            AccessibilityLabel::Glyphs => ("settings-glyphs", on_off(&locale, settings.glyphs)),
+            AccessibilityLabel::ScreenReader => (
+                "settings-screen-reader",
+                on_off(&locale, settings.screen_reader),
+            ),
+            AccessibilityLabel::FormCues => {
+                ("settings-form-cues", on_off(&locale, settings.form_cues))
+            }
+        };
+        set_label(
+            children,
+            &mut texts,
+            &locale.format(id, &[("value", value)]),
+        );
+    }
+}
+
+fn display_language(
+    locale: Res<Locale>,
+    labels: Query<&Children, With<LanguageLabel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let value = locale.format(
+        "settings-language",
+        &[("value", locale.language().name().into())],
+    );
+
+    for children in labels {
+        set_label(children, &mut texts, &value);
+    }
+}
+
+fn set_label(children: &Children, texts: &mut Query<&mut Text>, value: &str) {
+    let mut texts = texts.iter_many_mut(children);
+    while let Some(mut text) = texts.fetch_next() {
+        if text.0 != value {
+            text.0 = value.to_string();
+        }
+    }
+}
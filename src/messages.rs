@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{BoardIndex, Form},
+    rules::MatchShape,
+};
+
+/// Игрок или автоигра меняют местами две соседние фишки.
+#[derive(Message, Clone, Debug)]
+pub struct SwapRequested {
+    pub from: BoardIndex,
+    pub to: BoardIndex,
+}
+
+/// Обмен не дал совпадений, и фишки едут обратно.
+#[derive(Message, Clone, Debug)]
+pub struct SwapRejected {
+    pub from: BoardIndex,
+    pub to: BoardIndex,
+}
+
+/// Совпадение, которое сейчас снимут с доски. Пересекающиеся ряды одной формы приходят одним сообщением.
+#[derive(Message, Clone, Debug)]
+pub struct MatchFound {
+    /// Сущность доски, на которой нашлось совпадение.
+    pub board: Entity,
+    pub form: Form,
+    pub cells: Vec<BoardIndex>,
+    pub shape: MatchShape,
+}
+
+/// Фишки сняты с доски.
+#[derive(Message, Clone, Debug)]
+pub struct TilesCleared {
+    pub cells: Vec<BoardIndex>,
+}
+
+/// Фишки падают на освободившиеся клетки: откуда и куда.
+#[derive(Message, Clone, Debug)]
+pub struct TilesFell {
+    pub falls: Vec<(BoardIndex, BoardIndex)>,
+}
+
+/// Клавиатурный курсор перешёл на клетку. Формы нет, если клетка пуста.
+#[derive(Message, Clone, Debug)]
+pub struct CursorMoved {
+    pub cell: BoardIndex,
+    pub form: Option<Form>,
+}
+
+/// Всё упало и досчиталось, можно ходить.
+#[derive(Message, Clone, Debug)]
+pub struct BoardSettled;
+
+/// Счёт изменился: за совпадение, после отмены хода или новой партии.
+#[derive(Message, Clone, Debug)]
+pub struct ScoreChanged {
+    pub previous: usize,
+    pub score: usize,
+}
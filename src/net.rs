@@ -0,0 +1,584 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    env,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    AppState, GameMode, GameSession, GameState, GameplaySystems, ScoreStorage, Selection,
+    advance_tick,
+    board::{Board, BoardIndex},
+    board_settled, handle_selection,
+    locale::Locale,
+    menu::label,
+    messages::{BoardSettled, SwapRequested},
+    settings::Settings,
+    versus::Player,
+};
+
+const NET_MAGIC: &[u8; 4] = b"TMNP";
+const NET_VERSION: u8 = 1;
+const HELLO: u8 = 0;
+const SWAP: u8 = 1;
+const HASH: u8 = 2;
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Поединок по локальной сети в лок-степе.
+///
+/// Оба игрока гоняют одну и ту же детерминированную партию из одного зерна и пересылают только обмены
+/// с номером хода. Ход кончается, когда доска успокоилась, поэтому доска после хода зависит только
+/// от обменов, а не от того, на каком тике они пришли. После каждого успокоения стороны обмениваются
+/// хешем доски и так замечают рассинхронизацию.
+///
+/// Хозяин запускает игру с `--host <адрес:порт>`, второй игрок — с `--join <адрес:порт>`.
+/// Мусорные фишки сопернику за большие совпадения ждут блокираторов, которых пока нет.
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, connect_from_args)
+            .add_systems(Update, wait_for_peer.run_if(resource_exists::<Lobby>))
+            .add_systems(OnEnter(AppState::InGame), spawn_net_display)
+            .add_systems(OnExit(AppState::InGame), disconnect)
+            .add_systems(
+                Update,
+                (
+                    display_net,
+                    end_on_net_failure.run_if(in_state(GameState::Playing)),
+                )
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_exists::<NetPeer>),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        receive_messages,
+                        feed_remote_swap.run_if(board_settled).run_if(remote_turn),
+                    )
+                        .chain()
+                        .after(advance_tick)
+                        .before(handle_selection),
+                    (send_swaps, exchange_hashes).chain().after(GameplaySystems),
+                )
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_exists::<NetPeer>),
+            );
+    }
+}
+
+/// Сообщение протокола. Каждое — байт типа и поля фиксированной длины в little-endian.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NetMessage {
+    /// Хозяин сообщает зерно и скорость анимации партии. Перед ними идут `TMNP` и версия протокола.
+    Hello { seed: u64, animation_speed: f32 },
+    /// Обмен, сделанный на ходу `turn`. Ходы считаются с нуля по всем обменам обоих игроков.
+    Swap {
+        turn: u32,
+        from: BoardIndex,
+        to: BoardIndex,
+    },
+    /// Хеш успокоившейся доски и счёта после `turn` обменов.
+    Hash { turn: u32, hash: u64 },
+}
+
+impl NetMessage {
+    pub fn encode(&self, bytes: &mut Vec<u8>) {
+        match *self {
+            NetMessage::Hello {
+                seed,
+                animation_speed,
+            } => {
+                bytes.push(HELLO);
+                bytes.extend_from_slice(NET_MAGIC);
+                bytes.push(NET_VERSION);
+                bytes.extend_from_slice(&seed.to_le_bytes());
+                bytes.extend_from_slice(&animation_speed.to_le_bytes());
+            }
+            NetMessage::Swap { turn, from, to } => {
+                bytes.push(SWAP);
+                bytes.extend_from_slice(&turn.to_le_bytes());
+                for idx in [from, to] {
+                    bytes.push(idx.row_id() as u8);
+                    bytes.push(idx.col_id() as u8);
+                }
+            }
+            NetMessage::Hash { turn, hash } => {
+                bytes.push(HASH);
+                bytes.extend_from_slice(&turn.to_le_bytes());
+                bytes.extend_from_slice(&hash.to_le_bytes());
+            }
+        }
+    }
+
+    /// Читает сообщение из начала `bytes` и возвращает его длину. `None`, если сообщение пришло не целиком.
+    pub fn decode(bytes: &[u8]) -> io::Result<Option<(Self, usize)>> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let Some(kind) = bytes.first() else {
+            return Ok(None);
+        };
+        let len = match *kind {
+            HELLO => 18,
+            SWAP => 9,
+            HASH => 13,
+            _ => return Err(invalid("Unknown message")),
+        };
+        if bytes.len() < len {
+            return Ok(None);
+        }
+        let field = |range: std::ops::Range<usize>| &bytes[range];
+
+        let message = match *kind {
+            HELLO => {
+                if field(1..5) != NET_MAGIC {
+                    return Err(invalid("Not a tile matching peer"));
+                }
+                if bytes[5] != NET_VERSION {
+                    return Err(invalid(&format!(
+                        "Unsupported protocol version {} (expected {NET_VERSION})",
+                        bytes[5]
+                    )));
+                }
+
+                let animation_speed = f32::from_le_bytes(field(14..18).try_into().unwrap());
+                if !animation_speed.is_finite() || animation_speed <= 0. {
+                    return Err(invalid("Animation speed must be positive"));
+                }
+
+                NetMessage::Hello {
+                    seed: u64::from_le_bytes(field(6..14).try_into().unwrap()),
+                    animation_speed,
+                }
+            }
+            SWAP => {
+                let read_idx = |at: usize| -> io::Result<BoardIndex> {
+                    let (row_id, col_id) = (bytes[at] as usize, bytes[at + 1] as usize);
+                    if row_id >= Board::HEIGHT || col_id >= Board::WIDTH {
+                        return Err(invalid("Cell index is out of board"));
+                    }
+                    Ok((row_id, col_id).into())
+                };
+
+                NetMessage::Swap {
+                    turn: u32::from_le_bytes(field(1..5).try_into().unwrap()),
+                    from: read_idx(5)?,
+                    to: read_idx(7)?,
+                }
+            }
+            _ => NetMessage::Hash {
+                turn: u32::from_le_bytes(field(1..5).try_into().unwrap()),
+                hash: u64::from_le_bytes(field(5..13).try_into().unwrap()),
+            },
+        };
+
+        Ok(Some((message, len)))
+    }
+}
+
+/// Хеш доски вместе со скрытыми рядами и счёта обоих игроков. FNV-1a, чтобы он не зависел от версии Rust.
+pub fn board_hash(board: &Board, score: &ScoreStorage) -> u64 {
+    let state = format!("{board}{}/{}", score.of(Player::One), score.of(Player::Two));
+
+    state.bytes().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Неблокирующее TCP-соединение. Что не ушло или пришло не целиком, ждёт в буферах до следующего опроса.
+struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    /// Отправляет сообщение сразу, чтобы оно ушло, даже если опрашивать соединение больше не будут.
+    /// Ошибку отправки покажет следующий `poll`.
+    fn send(&mut self, message: &NetMessage) {
+        message.encode(&mut self.outgoing);
+        let _ = self.flush();
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(sent) => {
+                    self.outgoing.drain(..sent);
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Отправляет накопленное и забирает пришедшие сообщения. Закрытое соединение считается ошибкой.
+    fn poll(&mut self) -> io::Result<Vec<NetMessage>> {
+        self.flush()?;
+
+        let mut buf = [0; 1024];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(received) => self.incoming.extend_from_slice(&buf[..received]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some((message, len)) = NetMessage::decode(&self.incoming)? {
+            self.incoming.drain(..len);
+            messages.push(message);
+        }
+
+        Ok(messages)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetStatus {
+    Connected,
+    Disconnected,
+    /// Хеши досок разошлись после хода `turn` или соперник прислал ход не по порядку.
+    Desync {
+        turn: u32,
+    },
+}
+
+/// Соединение с соперником на время сетевой партии.
+#[derive(Resource)]
+pub struct NetPeer {
+    connection: Connection,
+    /// За кого играет этот экземпляр игры. Хозяин ходит первым.
+    local: Player,
+    status: NetStatus,
+    /// Сколько обменов сделано в партии обоими игроками, он же номер следующего хода.
+    turn: u32,
+    remote_swaps: VecDeque<(u32, BoardIndex, BoardIndex)>,
+    local_hashes: BTreeMap<u32, u64>,
+    remote_hashes: BTreeMap<u32, u64>,
+}
+
+impl NetPeer {
+    pub fn new(stream: TcpStream, local: Player) -> io::Result<Self> {
+        Ok(Self::with_connection(Connection::new(stream)?, local))
+    }
+
+    fn with_connection(connection: Connection, local: Player) -> Self {
+        Self {
+            connection,
+            local,
+            status: NetStatus::Connected,
+            turn: 0,
+            remote_swaps: VecDeque::new(),
+            local_hashes: BTreeMap::new(),
+            remote_hashes: BTreeMap::new(),
+        }
+    }
+
+    pub fn local(&self) -> Player {
+        self.local
+    }
+
+    pub fn status(&self) -> NetStatus {
+        self.status
+    }
+
+    fn handle(&mut self, messages: Vec<NetMessage>) {
+        for message in messages {
+            match message {
+                NetMessage::Hello { .. } => warn!("Opponent sent a second hello"),
+                NetMessage::Swap { turn, from, to } => {
+                    self.remote_swaps.push_back((turn, from, to))
+                }
+                NetMessage::Hash { turn, hash } => {
+                    self.remote_hashes.insert(turn, hash);
+                }
+            }
+        }
+
+        self.compare_hashes();
+    }
+
+    fn compare_hashes(&mut self) {
+        let turns = self
+            .local_hashes
+            .keys()
+            .filter(|turn| self.remote_hashes.contains_key(turn))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for turn in turns {
+            if self.local_hashes.remove(&turn) != self.remote_hashes.remove(&turn) {
+                self.desync(turn);
+            }
+        }
+    }
+
+    /// Запоминает только первую рассинхронизацию: дальше доски расходятся всё сильнее.
+    fn desync(&mut self, turn: u32) {
+        if self.status == NetStatus::Connected {
+            error!("Boards diverged on turn {turn}");
+            self.status = NetStatus::Desync { turn };
+        }
+    }
+}
+
+/// Сетевая партия, которая ещё не началась.
+#[derive(Resource)]
+enum Lobby {
+    /// Ждём, пока подключится второй игрок.
+    Hosting(TcpListener),
+    /// Подключились и ждём от хозяина зерно партии.
+    Joining(Connection),
+}
+
+fn arg_value(name: &str) -> Option<String> {
+    env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+/// Начинает ждать соперника по `--host <адрес>` или подключается к нему по `--join <адрес>`.
+fn connect_from_args(mut commands: Commands) {
+    let lobby = if let Some(address) = arg_value("--host") {
+        TcpListener::bind(&address).and_then(|listener| {
+            listener.set_nonblocking(true)?;
+            info!("Waiting for an opponent on {}", listener.local_addr()?);
+            Ok(Lobby::Hosting(listener))
+        })
+    } else if let Some(address) = arg_value("--join") {
+        TcpStream::connect(&address)
+            .and_then(Connection::new)
+            .map(Lobby::Joining)
+    } else {
+        return;
+    };
+
+    match lobby {
+        Ok(lobby) => commands.insert_resource(lobby),
+        Err(err) => error!("Failed to start LAN game: {err}"),
+    }
+}
+
+fn wait_for_peer(world: &mut World) {
+    let Some(lobby) = world.remove_resource::<Lobby>() else {
+        return;
+    };
+    let animation_speed = world.resource::<Settings>().animation_speed;
+
+    let game = match lobby {
+        Lobby::Hosting(listener) => match listener.accept() {
+            Ok((stream, address)) => {
+                info!("Opponent connected from {address}");
+                host_game(stream, animation_speed)
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                world.insert_resource(Lobby::Hosting(listener));
+                return;
+            }
+            Err(err) => Err(err),
+        },
+        Lobby::Joining(mut connection) => match connection.poll() {
+            Ok(messages) if messages.is_empty() => {
+                world.insert_resource(Lobby::Joining(connection));
+                return;
+            }
+            Ok(messages) => join_game(connection, messages),
+            Err(err) => Err(err),
+        },
+    };
+
+    match game {
+        Ok((session, peer)) => {
+            world.insert_resource(session);
+            world.insert_resource(peer);
+            world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::InGame);
+        }
+        Err(err) => error!("Failed to start LAN game: {err}"),
+    }
+}
+
+fn host_game(stream: TcpStream, animation_speed: f32) -> io::Result<(GameSession, NetPeer)> {
+    let session = GameSession::new(GameMode::Versus, animation_speed);
+    let mut peer = NetPeer::new(stream, Player::One)?;
+    peer.connection.send(&NetMessage::Hello {
+        seed: session.seed,
+        animation_speed,
+    });
+
+    Ok((session, peer))
+}
+
+fn join_game(
+    connection: Connection,
+    mut messages: Vec<NetMessage>,
+) -> io::Result<(GameSession, NetPeer)> {
+    let NetMessage::Hello {
+        seed,
+        animation_speed,
+    } = messages.remove(0)
+    else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Host did not send the game seed",
+        ));
+    };
+
+    let session = GameSession::new(GameMode::Versus, animation_speed).with_seed(seed);
+    let mut peer = NetPeer::with_connection(connection, Player::Two);
+    peer.handle(messages);
+
+    Ok((session, peer))
+}
+
+/// Сетевая партия одна: после выхода из неё соединение закрывается, и новая партия идёт за одним экраном.
+fn disconnect(mut commands: Commands) {
+    commands.remove_resource::<NetPeer>();
+}
+
+/// Сейчас ходит соперник по сети, и свой ввод ждёт.
+pub(crate) fn remote_turn(peer: Option<Res<NetPeer>>, session: Res<GameSession>) -> bool {
+    peer.is_some_and(|peer| {
+        session
+            .versus()
+            .is_some_and(|versus| versus.player() != peer.local)
+    })
+}
+
+fn receive_messages(mut peer: ResMut<NetPeer>) {
+    if peer.status != NetStatus::Connected {
+        return;
+    }
+
+    match peer.connection.poll() {
+        Ok(messages) => peer.handle(messages),
+        Err(err) => {
+            warn!("Opponent disconnected: {err}");
+            peer.status = NetStatus::Disconnected;
+        }
+    }
+}
+
+/// Подаёт ход соперника как два клика, когда доска готова его принять.
+fn feed_remote_swap(mut peer: ResMut<NetPeer>, mut selection: Single<&mut Selection>) {
+    let Some((turn, from, to)) = peer.remote_swaps.pop_front() else {
+        return;
+    };
+
+    if turn != peer.turn {
+        let expected = peer.turn;
+        peer.desync(expected);
+        return;
+    }
+
+    selection.request_swap(from, to);
+}
+
+/// Отправляет свои обмены и считает все, и свои, и соперника.
+fn send_swaps(
+    mut peer: ResMut<NetPeer>,
+    session: Res<GameSession>,
+    mut swaps: MessageReader<SwapRequested>,
+) {
+    for swap in swaps.read() {
+        let local = session
+            .versus()
+            .is_some_and(|versus| versus.player() == peer.local);
+
+        if local {
+            let turn = peer.turn;
+            peer.connection.send(&NetMessage::Swap {
+                turn,
+                from: swap.from,
+                to: swap.to,
+            });
+        }
+        peer.turn += 1;
+    }
+}
+
+fn exchange_hashes(
+    mut peer: ResMut<NetPeer>,
+    board: Single<&Board>,
+    score: Res<ScoreStorage>,
+    mut settled: MessageReader<BoardSettled>,
+) {
+    if settled.read().count() == 0 {
+        return;
+    }
+
+    let turn = peer.turn;
+    let hash = board_hash(&board, &score);
+    peer.local_hashes.insert(turn, hash);
+    peer.connection.send(&NetMessage::Hash { turn, hash });
+    peer.compare_hashes();
+}
+
+fn end_on_net_failure(peer: Res<NetPeer>, mut next_state: ResMut<NextState<GameState>>) {
+    if peer.status != NetStatus::Connected {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+#[derive(Component)]
+struct NetDisplay;
+
+fn spawn_net_display(mut commands: Commands, peer: Option<Res<NetPeer>>) {
+    if peer.is_none() {
+        return;
+    }
+
+    commands.spawn((
+        label(""),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(5),
+            right: px(5),
+            ..Default::default()
+        },
+        NetDisplay,
+        DespawnOnExit(AppState::InGame),
+    ));
+}
+
+fn display_net(
+    peer: Res<NetPeer>,
+    locale: Res<Locale>,
+    mut display: Single<&mut Text, With<NetDisplay>>,
+) {
+    let mut lines = vec![locale.format(
+        "net-you",
+        &[("player", locale.text(peer.local.message_id()).into())],
+    )];
+    match peer.status {
+        NetStatus::Connected => {}
+        NetStatus::Disconnected => lines.push(locale.text("net-disconnected")),
+        NetStatus::Desync { turn } => {
+            lines.push(locale.format("net-desync", &[("turn", turn.into())]));
+        }
+    }
+
+    display.0 = lines.join("\n");
+}
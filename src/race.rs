@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameSession, ScoreStorage, board::Board, locale::Locale, player_score_lines, versus::Player,
+};
+
+/// Кто первым наберёт столько очков, тот и выиграл гонку.
+pub const RACE_TARGET_SCORE: usize = 1000;
+/// Если цели никто не достиг, выигрывает тот, у кого больше очков к концу времени.
+pub(crate) const RACE_SECONDS: f32 = 180.;
+/// Промежуток между досками гонки в клетках.
+const BOARD_GAP_CELLS: f32 = 1.;
+
+/// Где стоит доска игрока: одна доска в центре, две — слева и справа от него.
+pub(crate) fn board_origin(player: Player, boards: usize) -> Vec2 {
+    if boards < 2 {
+        return Vec2::ZERO;
+    }
+
+    let step = Board::CELL_SIZE * (Board::WIDTH as f32 + BOARD_GAP_CELLS);
+    let offset = match player {
+        Player::One => -0.5,
+        Player::Two => 0.5,
+    };
+
+    Vec2::new(step * offset, 0.)
+}
+
+/// Отдаляет камеру, чтобы в кадр влезли все доски партии.
+pub(crate) fn fit_camera(
+    session: Res<GameSession>,
+    mut projection: Single<&mut Projection, With<Camera2d>>,
+) {
+    if let Projection::Orthographic(orthographic) = &mut **projection {
+        orthographic.scale = session.mode.board_owners().len() as f32;
+    }
+}
+
+pub(crate) fn race_lines(score: &ScoreStorage, locale: &Locale) -> Vec<String> {
+    let mut lines = player_score_lines(score, locale);
+    lines.push(locale.format("hud-target", &[("score", RACE_TARGET_SCORE.into())]));
+
+    lines
+}
@@ -0,0 +1,543 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState, GameMode, GameSession, GameState, GameTick, GameplaySystems, Selection, advance_tick,
+    board::{Board, BoardIndex},
+    board_settled, handle_selection,
+    locale::Locale,
+    menu::label,
+    savegame::SavedGame,
+    setup,
+    storage::StorageRoot,
+    undo::UndoHistory,
+};
+
+const REPLAYS_DIR: &str = "replays";
+const REPLAY_EXTENSION: &str = "tmr";
+const REPLAY_MAGIC: &[u8; 4] = b"TMRP";
+const REPLAY_VERSION: u8 = 3;
+/// Версия до появления скорости анимации в заголовке. Такие повторы шли на обычной скорости.
+const REPLAY_VERSION_WITHOUT_SPEED: u8 = 1;
+/// Версия до появления дня испытания в заголовке. Испытание дня из такого повтора идёт с сегодняшней датой.
+const REPLAY_VERSION_WITHOUT_DAY: u8 = 2;
+const PLAYBACK_SPEEDS: [f64; 5] = [0.5, 1., 2., 4., 8.];
+const NORMAL_SPEED_ID: usize = 1;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, play_replay_from_args)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (start_recording.before(setup), spawn_playback_display),
+            )
+            .add_systems(OnEnter(GameState::GameOver), finish_recording)
+            .add_systems(OnExit(AppState::InGame), finish_recording)
+            .add_systems(
+                OnTransition {
+                    exited: AppState::InGame,
+                    entered: AppState::MainMenu,
+                },
+                stop_playback,
+            )
+            .add_systems(
+                Update,
+                (playback_controls, display_playback)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_exists::<Playback>),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    feed_playback.after(advance_tick).before(handle_selection),
+                    finish_playback.after(GameplaySystems).run_if(board_settled),
+                )
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_exists::<Playback>),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum ReplayActionKind {
+    Swap(BoardIndex, BoardIndex),
+    Undo,
+}
+
+/// Действие игрока и тик `FixedUpdate`, на котором оно было выполнено.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ReplayAction {
+    pub tick: u32,
+    pub kind: ReplayActionKind,
+}
+
+/// Партия, которую можно воспроизвести заново: зерно доски и все принятые действия.
+///
+/// Файл `.tmr` хранит её в двоичном виде: заголовок `TMRP`, версия, режим, зерно, скорость анимации,
+/// день испытания (дни от начала эры, 0 — не испытание), уровень, затем действия по 5 байт (тик и тип),
+/// у обмена ещё 4 байта с индексами клеток.
+pub struct Replay {
+    mode: GameMode,
+    day: Option<NaiveDate>,
+    level: Option<String>,
+    seed: u64,
+    animation_speed: f32,
+    actions: Vec<ReplayAction>,
+}
+
+impl Replay {
+    fn new(session: &GameSession) -> Self {
+        Self {
+            mode: session.mode,
+            day: session.day(),
+            level: session.level_name().map(ToString::to_string),
+            seed: session.seed,
+            animation_speed: session.animation_speed,
+            actions: Vec::new(),
+        }
+    }
+
+    fn dir(root: &StorageRoot) -> Option<PathBuf> {
+        root.data_path(REPLAYS_DIR)
+    }
+
+    /// Самый свежий повтор: имена файлов — время записи, поэтому последний по имени и есть последний.
+    pub fn last_path(root: &StorageRoot) -> Option<PathBuf> {
+        fs::read_dir(Self::dir(root)?)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION))
+            .max()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    fn save(&self, root: &StorageRoot) -> io::Result<PathBuf> {
+        let dir = Self::dir(root).ok_or_else(|| io::Error::other("No data directory"))?;
+        fs::create_dir_all(&dir)?;
+
+        let file_name = format!(
+            "{}-{}.{REPLAY_EXTENSION}",
+            Local::now().format("%Y-%m-%d_%H-%M-%S"),
+            self.mode.name().to_lowercase()
+        );
+        let path = dir.join(file_name);
+
+        fs::write(&path, self.encode()?)?;
+        Ok(path)
+    }
+
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        let mode = mode_id(self.mode).ok_or_else(|| {
+            io::Error::other(format!(
+                "{} games can't be stored in a replay",
+                self.mode.name()
+            ))
+        })?;
+        let level = self.level.as_deref().unwrap_or_default().as_bytes();
+        let mut bytes = Vec::with_capacity(28 + level.len() + self.actions.len() * 9);
+
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.push(REPLAY_VERSION);
+        bytes.push(mode);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.animation_speed.to_le_bytes());
+        bytes.extend_from_slice(
+            &self
+                .day
+                .map_or(0, |day| day.num_days_from_ce())
+                .to_le_bytes(),
+        );
+        bytes.extend_from_slice(&(level.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(level);
+        bytes.extend_from_slice(&(self.actions.len() as u32).to_le_bytes());
+
+        for action in &self.actions {
+            bytes.extend_from_slice(&action.tick.to_le_bytes());
+
+            match action.kind {
+                ReplayActionKind::Swap(from, to) => {
+                    bytes.push(0);
+                    for idx in [from, to] {
+                        bytes.push(idx.row_id() as u8);
+                        bytes.push(idx.col_id() as u8);
+                    }
+                }
+                ReplayActionKind::Undo => bytes.push(1),
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let mut magic = [0; 4];
+        bytes.read_exact(&mut magic)?;
+        if &magic != REPLAY_MAGIC {
+            return Err(invalid("Not a replay file"));
+        }
+
+        let version = read_u8(&mut bytes)?;
+        if ![
+            REPLAY_VERSION,
+            REPLAY_VERSION_WITHOUT_DAY,
+            REPLAY_VERSION_WITHOUT_SPEED,
+        ]
+        .contains(&version)
+        {
+            return Err(invalid(&format!(
+                "Unsupported replay version {version} (expected {REPLAY_VERSION})"
+            )));
+        }
+
+        let mode = *GameMode::ALL
+            .get(read_u8(&mut bytes)? as usize)
+            .ok_or_else(|| invalid("Unknown game mode"))?;
+
+        let mut seed = [0; 8];
+        bytes.read_exact(&mut seed)?;
+        let seed = u64::from_le_bytes(seed);
+
+        let animation_speed = if version == REPLAY_VERSION_WITHOUT_SPEED {
+            1.
+        } else {
+            let mut speed = [0; 4];
+            bytes.read_exact(&mut speed)?;
+            f32::from_le_bytes(speed)
+        };
+        if !animation_speed.is_finite() || animation_speed <= 0. {
+            return Err(invalid("Animation speed must be positive"));
+        }
+
+        let day = if version == REPLAY_VERSION {
+            let mut day = [0; 4];
+            bytes.read_exact(&mut day)?;
+            match i32::from_le_bytes(day) {
+                0 => None,
+                days => Some(
+                    NaiveDate::from_num_days_from_ce_opt(days)
+                        .ok_or_else(|| invalid("Challenge day is out of range"))?,
+                ),
+            }
+        } else {
+            None
+        };
+
+        let mut level_len = [0; 2];
+        bytes.read_exact(&mut level_len)?;
+        let mut level = vec![0; u16::from_le_bytes(level_len) as usize];
+        bytes.read_exact(&mut level)?;
+        let level = String::from_utf8(level).map_err(|_| invalid("Level name is not UTF-8"))?;
+
+        let read_idx = |bytes: &mut &[u8]| -> io::Result<BoardIndex> {
+            let (row_id, col_id) = (read_u8(bytes)? as usize, read_u8(bytes)? as usize);
+            if row_id >= Board::HEIGHT || col_id >= Board::WIDTH {
+                return Err(invalid("Cell index is out of board"));
+            }
+            Ok((row_id, col_id).into())
+        };
+
+        let count = read_u32(&mut bytes)?;
+        let mut actions = Vec::new();
+        for _ in 0..count {
+            let tick = read_u32(&mut bytes)?;
+            let kind = match read_u8(&mut bytes)? {
+                0 => ReplayActionKind::Swap(read_idx(&mut bytes)?, read_idx(&mut bytes)?),
+                1 => ReplayActionKind::Undo,
+                _ => return Err(invalid("Unknown replay action")),
+            };
+
+            actions.push(ReplayAction { tick, kind });
+        }
+
+        Ok(Self {
+            mode,
+            day,
+            level: (!level.is_empty()).then_some(level),
+            seed,
+            animation_speed,
+            actions,
+        })
+    }
+}
+
+/// Номер режима в заголовке. Режимов не из `GameMode::ALL`, например кампании, повтор не хранит.
+fn mode_id(mode: GameMode) -> Option<u8> {
+    GameMode::ALL
+        .iter()
+        .position(|other| *other == mode)
+        .map(|id| id as u8)
+}
+
+fn read_u8(bytes: &mut &[u8]) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    bytes.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(bytes: &mut &[u8]) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    bytes.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Повтор текущей партии, который пишется, пока в неё играют.
+#[derive(Resource)]
+pub struct ReplayRecorder(Replay);
+
+impl ReplayRecorder {
+    pub fn record_swap(&mut self, tick: u32, from: BoardIndex, to: BoardIndex) {
+        self.0.actions.push(ReplayAction {
+            tick,
+            kind: ReplayActionKind::Swap(from, to),
+        });
+    }
+
+    pub fn record_undo(&mut self, tick: u32) {
+        self.0.actions.push(ReplayAction {
+            tick,
+            kind: ReplayActionKind::Undo,
+        });
+    }
+
+    pub fn actions(&self) -> &[ReplayAction] {
+        &self.0.actions
+    }
+}
+
+fn start_recording(
+    mut commands: Commands,
+    session: Res<GameSession>,
+    saved: Option<Res<SavedGame>>,
+    playback: Option<Res<Playback>>,
+) {
+    // Действия в повторе не знают своей доски, поэтому гонку на двух досках не записываем.
+    // Уровень повтор знает только по имени, а найти уровень по имени пока негде.
+    // Режим, которому нет номера в заголовке, повтор не сохранит.
+    if playback.is_some()
+        || session.mode.board_owners().len() > 1
+        || session.level().is_some()
+        || mode_id(session.mode).is_none()
+    {
+        return;
+    }
+
+    let mut replay = Replay::new(&session);
+    if let Some(saved) = saved {
+        replay.actions.clone_from(&saved.actions);
+    }
+
+    commands.insert_resource(ReplayRecorder(replay));
+}
+
+fn finish_recording(
+    mut commands: Commands,
+    recorder: Option<Res<ReplayRecorder>>,
+    root: Res<StorageRoot>,
+) {
+    let Some(recorder) = recorder else {
+        return;
+    };
+    commands.remove_resource::<ReplayRecorder>();
+
+    if recorder.0.actions.is_empty() {
+        return;
+    }
+
+    match recorder.0.save(&root) {
+        Ok(path) => info!("Replay saved to {}", path.display()),
+        Err(err) => error!("Failed to save replay: {err}"),
+    }
+}
+
+/// Воспроизводимый повтор. Пока ресурс вставлен, клики и отмена с клавиатуры отключены.
+#[derive(Resource)]
+pub struct Playback {
+    replay: Replay,
+    next_action: usize,
+    speed_id: usize,
+}
+
+impl Playback {
+    fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_action: 0,
+            speed_id: NORMAL_SPEED_ID,
+        }
+    }
+
+    pub fn session(&self) -> GameSession {
+        let mut session = GameSession::new(self.replay.mode, self.replay.animation_speed);
+
+        if let Some(day) = self.replay.day {
+            session = session.with_day(day);
+        }
+        session.seed = self.replay.seed;
+
+        session
+    }
+
+    pub fn rewind(&mut self) {
+        self.next_action = 0;
+    }
+
+    fn speed(&self) -> f64 {
+        PLAYBACK_SPEEDS[self.speed_id]
+    }
+
+    fn is_finished(&self) -> bool {
+        self.next_action == self.replay.actions.len()
+    }
+}
+
+fn start_playback(commands: &mut Commands, next_state: &mut NextState<AppState>, replay: Replay) {
+    let playback = Playback::new(replay);
+
+    commands.insert_resource(playback.session());
+    commands.insert_resource(playback);
+    next_state.set(AppState::InGame);
+}
+
+pub fn watch_last_replay(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+    root: Res<StorageRoot>,
+) {
+    let Some(path) = Replay::last_path(&root) else {
+        return;
+    };
+
+    match Replay::load(&path) {
+        Ok(replay) => start_playback(&mut commands, &mut next_state, replay),
+        Err(err) => error!("Failed to load replay {}: {err}", path.display()),
+    }
+}
+
+/// Запускает повтор из `--replay <path>`, минуя главное меню.
+fn play_replay_from_args(mut commands: Commands, mut next_state: ResMut<NextState<AppState>>) {
+    let Some(path) = env::args().skip_while(|arg| arg != "--replay").nth(1) else {
+        return;
+    };
+
+    match Replay::load(Path::new(&path)) {
+        Ok(replay) => start_playback(&mut commands, &mut next_state, replay),
+        Err(err) => error!("Failed to load replay {path}: {err}"),
+    }
+}
+
+fn stop_playback(mut commands: Commands, mut time: ResMut<Time<Virtual>>) {
+    commands.remove_resource::<Playback>();
+    time.set_relative_speed_f64(1.);
+}
+
+/// Подаёт записанные действия тем же путём, что и игрок: обмен — как два клика, отмену — как Ctrl+Z.
+fn feed_playback(
+    mut playback: ResMut<Playback>,
+    tick: Res<GameTick>,
+    mut selection: Single<&mut Selection>,
+    mut undo_history: ResMut<UndoHistory>,
+) {
+    while let Some(action) = playback
+        .replay
+        .actions
+        .get(playback.next_action)
+        .filter(|action| action.tick <= tick.0)
+        .copied()
+    {
+        match action.kind {
+            ReplayActionKind::Swap(from, to) => selection.request_swap(from, to),
+            ReplayActionKind::Undo => undo_history.request(),
+        }
+
+        playback.next_action += 1;
+    }
+}
+
+fn finish_playback(
+    playback: Res<Playback>,
+    undo_history: Res<UndoHistory>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if playback.is_finished() && !undo_history.is_requested() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn playback_controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut playback: ResMut<Playback>,
+    mut time: ResMut<Time<Virtual>>,
+    state: Res<State<GameState>>,
+) {
+    if keys.just_pressed(KeyCode::Minus) {
+        playback.speed_id = playback.speed_id.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::Equal) {
+        playback.speed_id = (playback.speed_id + 1).min(PLAYBACK_SPEEDS.len() - 1);
+    }
+    if time.relative_speed_f64() != playback.speed() {
+        time.set_relative_speed_f64(playback.speed());
+    }
+
+    if keys.just_pressed(KeyCode::Space) && *state.get() == GameState::Playing {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+}
+
+#[derive(Component)]
+struct PlaybackDisplay;
+
+fn spawn_playback_display(mut commands: Commands, playback: Option<Res<Playback>>) {
+    if playback.is_none() {
+        return;
+    }
+
+    commands.spawn((
+        label(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(5),
+            right: px(5),
+            ..Default::default()
+        },
+        PlaybackDisplay,
+        DespawnOnExit(AppState::InGame),
+    ));
+}
+
+fn display_playback(
+    playback: Res<Playback>,
+    time: Res<Time<Virtual>>,
+    locale: Res<Locale>,
+    mut display: Single<&mut Text, With<PlaybackDisplay>>,
+) {
+    let status = if time.is_paused() {
+        "replay-paused"
+    } else {
+        "replay-status"
+    };
+
+    display.0 = format!(
+        "{}\n{}",
+        locale.format(status, &[("speed", playback.speed().into())]),
+        locale.text("replay-help")
+    );
+}
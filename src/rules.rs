@@ -0,0 +1,440 @@
+use rand::Rng;
+
+use crate::board::{Board, BoardIndex, CellKind, Form};
+
+/// Очки за каждую фишку в совпавшем ряду.
+pub const SCORE_PER_TILE: usize = 10;
+pub const MIN_RUN_LEN: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+/// Непрерывный ряд из трёх и более одинаковых форм.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Run {
+    pub form: Form,
+    pub direction: Direction,
+    pub cells: Vec<BoardIndex>,
+}
+
+impl Run {
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+/// Фигура совпадения: один ряд или несколько рядов одной формы с общими клетками.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum MatchShape {
+    Line,
+    /// Ряды сходятся концами.
+    L,
+    /// Конец одного ряда упирается в середину другого.
+    T,
+    /// Ряды пересекаются серединами.
+    Cross,
+}
+
+/// Ряды одной формы, которые касаются друг друга, вместе.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Match {
+    pub form: Form,
+    pub shape: MatchShape,
+    pub cells: Vec<BoardIndex>,
+}
+
+/// Чем закончился ход: очки и ряды, снятые за каждую волну каскада.
+#[derive(Default, Debug)]
+pub struct Resolution {
+    pub score: usize,
+    pub rounds: Vec<Vec<Run>>,
+}
+
+impl Resolution {
+    pub fn cascade_len(&self) -> usize {
+        self.rounds.len()
+    }
+}
+
+/// Доска из одних форм, без сущностей Bevy, чтобы правила можно было гонять без окна.
+///
+/// Как и у `Board`, нижние `visible_height` рядов видны игроку, а над ними столько же скрытых,
+/// из которых падают новые фишки. Дыры и преграды пустые и никогда не заполняются.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Grid {
+    width: usize,
+    visible_height: usize,
+    form_count: usize,
+    cells: Vec<Vec<Option<Form>>>,
+    kinds: Vec<Vec<CellKind>>,
+}
+
+impl Grid {
+    pub fn new(width: usize, visible_height: usize, form_count: usize) -> Self {
+        assert!(
+            (1..=Form::ALL.len()).contains(&form_count),
+            "Form count must be between 1 and {}",
+            Form::ALL.len()
+        );
+
+        Self {
+            width,
+            visible_height,
+            form_count,
+            cells: vec![vec![None; width]; visible_height * 2],
+            kinds: vec![vec![CellKind::Open; width]; visible_height * 2],
+        }
+    }
+
+    /// Заполняет доску в том же порядке, что и `setup`, поэтому с тем же ГПСЧ раскладка совпадает с игрой.
+    pub fn random(
+        width: usize,
+        visible_height: usize,
+        form_count: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut grid = Self::new(width, visible_height, form_count);
+
+        for row in &mut grid.cells {
+            for cell in row {
+                *cell = Some(random_form(rng, form_count));
+            }
+        }
+
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn visible_height(&self) -> usize {
+        self.visible_height
+    }
+
+    pub fn form_count(&self) -> usize {
+        self.form_count
+    }
+
+    pub fn get(&self, idx: BoardIndex) -> Option<Form> {
+        self.cells[idx.row_id()][idx.col_id()]
+    }
+
+    pub fn set(&mut self, idx: BoardIndex, form: Option<Form>) {
+        self.cells[idx.row_id()][idx.col_id()] = form;
+    }
+
+    pub fn kind(&self, idx: BoardIndex) -> CellKind {
+        self.kinds[idx.row_id()][idx.col_id()]
+    }
+
+    /// Ставит в клетку дыру или преграду; фишка из неё пропадает.
+    pub fn set_kind(&mut self, idx: BoardIndex, kind: CellKind) {
+        self.kinds[idx.row_id()][idx.col_id()] = kind;
+        if !kind.is_open() {
+            self.set(idx, None);
+        }
+    }
+
+    pub fn swap(&mut self, a: BoardIndex, b: BoardIndex) {
+        let form = self.get(a);
+
+        self.set(a, self.get(b));
+        self.set(b, form);
+    }
+
+    /// Все максимальные ряды из трёх и более одинаковых форм в видимой части.
+    pub fn find_runs(&self) -> Vec<Run> {
+        self.runs_in_lines(0..self.visible_height, 0..self.width)
+    }
+
+    /// Ряды в заданных видимых строках и столбцах.
+    fn runs_in_lines(
+        &self,
+        row_ids: impl IntoIterator<Item = usize>,
+        col_ids: impl IntoIterator<Item = usize>,
+    ) -> Vec<Run> {
+        let mut runs = Vec::new();
+
+        for row_id in row_ids {
+            self.scan_line(
+                (0..self.width).map(|col_id| (row_id, col_id).into()),
+                Direction::Horizontal,
+                &mut runs,
+            );
+        }
+        for col_id in col_ids {
+            self.scan_line(
+                (0..self.visible_height).map(|row_id| (row_id, col_id).into()),
+                Direction::Vertical,
+                &mut runs,
+            );
+        }
+
+        runs
+    }
+
+    fn scan_line(
+        &self,
+        line: impl Iterator<Item = BoardIndex>,
+        direction: Direction,
+        runs: &mut Vec<Run>,
+    ) {
+        let mut current: Option<Run> = None;
+
+        for idx in line {
+            let form = self.get(idx);
+
+            match current.as_mut() {
+                Some(run) if Some(run.form) == form => run.cells.push(idx),
+                _ => {
+                    runs.extend(current.take().filter(|run| run.len() >= MIN_RUN_LEN));
+                    current = form.map(|form| Run {
+                        form,
+                        direction,
+                        cells: vec![idx],
+                    });
+                }
+            }
+        }
+
+        runs.extend(current.filter(|run| run.len() >= MIN_RUN_LEN));
+    }
+
+    /// Соседние видимые клетки с фишками, обмен которых даёт совпадение.
+    pub fn valid_swaps(&self) -> Vec<(BoardIndex, BoardIndex)> {
+        let mut swaps = Vec::new();
+
+        for row_id in 0..self.visible_height {
+            for col_id in 0..self.width {
+                let idx = (row_id, col_id).into();
+                let neighbours = [(row_id, col_id + 1), (row_id + 1, col_id)]
+                    .into_iter()
+                    .filter(|(row_id, col_id)| {
+                        *row_id < self.visible_height && *col_id < self.width
+                    });
+
+                for neighbour in neighbours {
+                    let neighbour = neighbour.into();
+                    if !self.runs_after_swap(idx, neighbour).is_empty() {
+                        swaps.push((idx, neighbour));
+                    }
+                }
+            }
+        }
+
+        swaps
+    }
+
+    /// Ряды, которые появятся после обмена и проходят через одну из обменянных клеток.
+    pub fn runs_after_swap(&self, a: BoardIndex, b: BoardIndex) -> Vec<Run> {
+        if self.get(a).is_none() || self.get(b).is_none() || self.get(a) == self.get(b) {
+            return Vec::new();
+        }
+
+        let mut swapped = self.clone();
+        swapped.swap(a, b);
+        swapped.runs_through(&[a, b])
+    }
+
+    /// Ряды, которые проходят через одну из клеток `cells`. Сканируются только их строки и столбцы.
+    pub fn runs_through(&self, cells: &[BoardIndex]) -> Vec<Run> {
+        let mut row_ids: Vec<_> = cells
+            .iter()
+            .map(BoardIndex::row_id)
+            .filter(|row_id| *row_id < self.visible_height)
+            .collect();
+        let mut col_ids: Vec<_> = cells.iter().map(BoardIndex::col_id).collect();
+        row_ids.sort_unstable();
+        row_ids.dedup();
+        col_ids.sort_unstable();
+        col_ids.dedup();
+
+        self.runs_in_lines(row_ids, col_ids)
+            .into_iter()
+            .filter(|run| cells.iter().any(|idx| run.cells.contains(idx)))
+            .collect()
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.valid_swaps().is_empty()
+    }
+
+    /// Обменивает фишки и разрешает все каскады. Обмен без совпадений откатывается, и возвращается `None`.
+    pub fn try_swap(
+        &mut self,
+        a: BoardIndex,
+        b: BoardIndex,
+        rng: &mut impl Rng,
+    ) -> Option<Resolution> {
+        let di = a.row_id().abs_diff(b.row_id());
+        let dj = a.col_id().abs_diff(b.col_id());
+        if di + dj != 1 || self.runs_after_swap(a, b).is_empty() {
+            return None;
+        }
+
+        self.swap(a, b);
+        Some(self.resolve(rng))
+    }
+
+    /// Снимает совпадения волна за волной, пока доска не успокоится.
+    pub fn resolve(&mut self, rng: &mut impl Rng) -> Resolution {
+        let mut resolution = Resolution::default();
+
+        loop {
+            let runs = self.find_runs();
+            if runs.is_empty() {
+                return resolution;
+            }
+
+            for run in &runs {
+                resolution.score += SCORE_PER_TILE * run.len();
+                for idx in &run.cells {
+                    self.set(*idx, None);
+                }
+            }
+            resolution.rounds.push(runs);
+
+            self.collapse();
+            self.refill(rng);
+        }
+    }
+
+    /// Роняет фишки в каждой колонке на пустые клетки под ними, сквозь дыры и преграды.
+    pub fn collapse(&mut self) {
+        for col_id in 0..self.width {
+            let open_rows: Vec<_> = (0..self.height())
+                .filter(|row_id| self.kinds[*row_id][col_id].is_open())
+                .collect();
+            let mut targets = open_rows.iter();
+
+            for row_id in &open_rows {
+                if let Some(form) = self.cells[*row_id][col_id].take() {
+                    let target = targets.next().expect("Column has more tiles than cells");
+                    self.cells[*target][col_id] = Some(form);
+                }
+            }
+        }
+    }
+
+    /// Заменяет скрытые ряды случайными фишками: так выглядит доска для того, кто их не видит.
+    pub fn randomize_hidden(&mut self, rng: &mut impl Rng) {
+        let hidden = self.cells[self.visible_height..]
+            .iter_mut()
+            .zip(&self.kinds[self.visible_height..]);
+        for (row, kinds) in hidden {
+            for (cell, kind) in row.iter_mut().zip(kinds) {
+                if kind.is_open() {
+                    *cell = Some(random_form(rng, self.form_count));
+                }
+            }
+        }
+    }
+
+    /// Заполняет пустые скрытые клетки в том же порядке, что и `spawn_tiles`.
+    pub fn refill(&mut self, rng: &mut impl Rng) {
+        for col_id in 0..self.width {
+            for row_id in self.visible_height..self.height() {
+                if self.cells[row_id][col_id].is_none() && self.kinds[row_id][col_id].is_open() {
+                    self.cells[row_id][col_id] = Some(random_form(rng, self.form_count));
+                }
+            }
+        }
+    }
+}
+
+impl From<&Board> for Grid {
+    fn from(board: &Board) -> Self {
+        Self {
+            width: board.width(),
+            visible_height: board.visible_height(),
+            form_count: Form::ALL.len(),
+            cells: board
+                .into_iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| cell.tile.map(|tile| tile.form))
+                        .collect()
+                })
+                .collect(),
+            kinds: board
+                .into_iter()
+                .map(|row| row.iter().map(|cell| cell.kind).collect())
+                .collect(),
+        }
+    }
+}
+
+/// Случайная форма из первых `form_count`. При всех пяти формах совпадает с `rng.random::<Form>()`.
+pub fn random_form(rng: &mut (impl Rng + ?Sized), form_count: usize) -> Form {
+    Form::ALL[rng.random_range(0..form_count)]
+}
+
+/// Склеивает пересекающиеся ряды одной формы в совпадения.
+pub fn group_runs(runs: &[Run]) -> Vec<Match> {
+    let mut groups: Vec<Vec<&Run>> = Vec::new();
+
+    for run in runs {
+        let (touching, mut rest): (Vec<_>, Vec<_>) = groups.into_iter().partition(|group| {
+            group.iter().any(|other| {
+                other.form == run.form && other.cells.iter().any(|idx| run.cells.contains(idx))
+            })
+        });
+
+        let mut group: Vec<_> = touching.into_iter().flatten().collect();
+        group.push(run);
+        rest.push(group);
+        groups = rest;
+    }
+
+    groups
+        .into_iter()
+        .map(|group| {
+            let mut cells = Vec::new();
+            for idx in group.iter().flat_map(|run| &run.cells) {
+                if !cells.contains(idx) {
+                    cells.push(*idx);
+                }
+            }
+
+            Match {
+                form: group[0].form,
+                shape: shape(&group),
+                cells,
+            }
+        })
+        .collect()
+}
+
+fn shape(runs: &[&Run]) -> MatchShape {
+    let is_end = |run: &Run, idx: &BoardIndex| {
+        run.cells.first() == Some(idx) || run.cells.last() == Some(idx)
+    };
+    let mut shape = MatchShape::Line;
+
+    for a in runs {
+        for b in runs.iter().filter(|b| b.direction != a.direction) {
+            for idx in a.cells.iter().filter(|idx| b.cells.contains(idx)) {
+                let crossing = match (is_end(a, idx), is_end(b, idx)) {
+                    (true, true) => MatchShape::L,
+                    (false, false) => MatchShape::Cross,
+                    _ => MatchShape::T,
+                };
+                shape = shape.max(crossing);
+            }
+        }
+    }
+
+    shape
+}
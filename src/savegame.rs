@@ -0,0 +1,324 @@
+use std::{io, path::PathBuf};
+
+use bevy::prelude::*;
+use chrono::NaiveDate;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState, GameMode, GameRng, GameSession, GameState, GameTick, GameplaySystems, ScoreStorage,
+    board::{Board, Form},
+    board_settled,
+    campaign::CampaignProgress,
+    editor::LevelEditor,
+    level::{Collected, Level},
+    replay::{Playback, ReplayAction, ReplayRecorder},
+    storage::{self, StorageRoot},
+};
+
+const SAVE_FILE: &str = "savegame.ron";
+const SAVE_VERSION: u32 = 3;
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        let root = app
+            .init_resource::<StorageRoot>()
+            .world()
+            .resource::<StorageRoot>();
+        let SaveFile { game, campaign, .. } = SaveFile::load(root);
+
+        // Пробная игра из редактора не трогает отложенную партию: ни сохраняет поверх, ни удаляет.
+        app.insert_resource(ContinueSlot(game))
+            .insert_resource(campaign)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                delete_save
+                    .run_if(not(resource_exists::<Playback>))
+                    .run_if(not(resource_exists::<LevelEditor>)),
+            )
+            .add_systems(
+                FixedUpdate,
+                autosave
+                    .after(GameplaySystems)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(resource_exists::<LevelEditor>))
+                    .run_if(condition_changed_to(true, board_settled)),
+            )
+            .add_systems(
+                Last,
+                save_campaign_progress
+                    .run_if(resource_changed::<CampaignProgress>)
+                    .run_if(not(resource_added::<CampaignProgress>)),
+            );
+    }
+}
+
+/// Файл сохранения: отложенная партия и прогресс кампании.
+#[derive(Serialize, Deserialize, Default)]
+struct SaveFile {
+    version: u32,
+    #[serde(default)]
+    game: Option<SavedGame>,
+    #[serde(default)]
+    campaign: CampaignProgress,
+}
+
+impl SaveFile {
+    fn path(root: &StorageRoot) -> Option<PathBuf> {
+        root.data_path(SAVE_FILE)
+    }
+
+    /// Загружает сохранение. Испорченный или несовместимый файл откладывается в сторону.
+    fn load(root: &StorageRoot) -> Self {
+        let Some(path) = Self::path(root) else {
+            return Self::default();
+        };
+
+        let mut file = match storage::read_ron::<Self>(&path) {
+            Ok(file) if file.version == SAVE_VERSION => file,
+            Ok(file) => {
+                warn!(
+                    "Unsupported save version {} in {} (expected {SAVE_VERSION})",
+                    file.version,
+                    path.display()
+                );
+                storage::back_up(&path);
+                return Self::default();
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                warn!("Failed to read save from {}: {err}", path.display());
+                storage::back_up(&path);
+                return Self::default();
+            }
+        };
+
+        // Партия на доске другого размера не продолжится, но прогресс кампании остаётся.
+        if file.game.as_ref().is_some_and(|game| {
+            game.board.len() != Board::HEIGHT
+                || game.board.iter().any(|row| row.len() != Board::WIDTH)
+        }) {
+            warn!("Saved game in {} does not fit the board", path.display());
+            file.game = None;
+        }
+
+        file
+    }
+
+    /// Переписывает файл целиком: отложенную партию и прогресс кампании.
+    fn write(root: &StorageRoot, game: Option<&SavedGame>, campaign: &CampaignProgress) {
+        let Some(path) = Self::path(root) else {
+            error!("Failed to save: no data directory");
+            return;
+        };
+        let file = Self {
+            version: SAVE_VERSION,
+            game: game.cloned(),
+            campaign: campaign.clone(),
+        };
+
+        if let Err(err) = storage::write_ron(&path, &file) {
+            error!("Failed to save {}: {err}", path.display());
+        }
+    }
+}
+
+/// Полное состояние успокоившейся доски, достаточное чтобы продолжить партию после перезапуска.
+///
+/// Пока ресурс вставлен, `setup` строит доску из него, а не из зерна.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct SavedGame {
+    mode: GameMode,
+    level: Option<Level>,
+    #[serde(default)]
+    level_path: Option<String>,
+    #[serde(default)]
+    day: Option<NaiveDate>,
+    /// Сохранения старых версий дня начала не знают, такая партия считается начатой при продолжении.
+    #[serde(default)]
+    started_on: Option<NaiveDate>,
+    seed: u64,
+    score: usize,
+    moves_made: u32,
+    moves_left: Option<u32>,
+    time_left: Option<f32>,
+    undos_left: Option<u32>,
+    #[serde(default)]
+    collected: Collected,
+    #[serde(default)]
+    autoplayed: bool,
+    /// Сохранения до появления настройки скорости анимации шли на обычной скорости.
+    #[serde(default = "normal_animation_speed")]
+    animation_speed: f32,
+    pub rng: ChaCha8Rng,
+    /// Формы по клеткам, включая скрытые ряды. На успокоившейся доске пусты только дыры и преграды.
+    pub board: Vec<Vec<Option<Form>>>,
+    #[serde(default)]
+    pub tick: u32,
+    /// Записанные с начала партии действия, чтобы продолженная партия дописывала тот же повтор.
+    #[serde(default)]
+    pub actions: Vec<ReplayAction>,
+}
+
+fn normal_animation_speed() -> f32 {
+    1.
+}
+
+impl SavedGame {
+    pub(crate) fn capture(
+        session: &GameSession,
+        score: &ScoreStorage,
+        rng: &GameRng,
+        board: &Board,
+    ) -> Option<Self> {
+        let board = board
+            .into_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell.tile {
+                        Some(tile) => Some(Some(tile.form)),
+                        None if !cell.kind.is_open() => Some(None),
+                        None => None,
+                    })
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self {
+            mode: session.mode,
+            level: session.level.clone(),
+            level_path: session.level_path.clone(),
+            day: session.day,
+            started_on: Some(session.started_on),
+            seed: session.seed,
+            score: score.total(),
+            moves_made: session.moves_made,
+            moves_left: session.moves_left,
+            time_left: session.time_left.as_ref().map(Timer::remaining_secs),
+            undos_left: session.undos_left,
+            collected: session.collected,
+            autoplayed: session.autoplayed,
+            animation_speed: session.animation_speed,
+            rng: rng.0.clone(),
+            board,
+            tick: 0,
+            actions: Vec::new(),
+        })
+    }
+
+    pub fn session(&self) -> GameSession {
+        let mut session = GameSession::new(self.mode, self.animation_speed);
+
+        session.level.clone_from(&self.level);
+        session.level_path.clone_from(&self.level_path);
+        session.day = self.day;
+        if let Some(day) = self.started_on {
+            session.started_on = day;
+        }
+        session.seed = self.seed;
+        session.moves_made = self.moves_made;
+        session.moves_left = self.moves_left;
+        session.time_left = self
+            .time_left
+            .map(|secs| Timer::from_seconds(secs, TimerMode::Once));
+        session.undos_left = self.undos_left;
+        session.collected = self.collected;
+        session.autoplayed = self.autoplayed;
+
+        session
+    }
+
+    pub fn score(&self) -> ScoreStorage {
+        ScoreStorage::solo(self.score)
+    }
+
+    /// Откатывает счёт, ходы, собранные фишки и ГПСЧ к снимку. Оставшиеся время и отмены не возвращаются.
+    pub(crate) fn rewind(
+        &self,
+        session: &mut GameSession,
+        score: &mut ScoreStorage,
+        rng: &mut GameRng,
+    ) {
+        session.moves_made = self.moves_made;
+        session.moves_left = self.moves_left;
+        session.collected = self.collected;
+        *score = ScoreStorage::solo(self.score);
+        rng.0.clone_from(&self.rng);
+    }
+}
+
+/// Партия, которую можно продолжить из главного меню.
+#[derive(Resource)]
+pub struct ContinueSlot(pub Option<SavedGame>);
+
+pub(crate) fn autosave(
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    board: Single<(&Board, &GameRng)>,
+    tick: Res<GameTick>,
+    recorder: Option<Res<ReplayRecorder>>,
+    playback: Option<Res<Playback>>,
+    mut slot: ResMut<ContinueSlot>,
+    campaign: Res<CampaignProgress>,
+    root: Res<StorageRoot>,
+) {
+    // Законченную партию продолжать нечего, её сохранение удалит `delete_save`.
+    // Просмотр повтора не должен затирать сохранённую партию.
+    // Поединок и гонка не сохраняются: в сохранении одиночный счёт и одна доска.
+    if session.is_over() || playback.is_some() || session.mode.is_duel() {
+        return;
+    }
+
+    let (board, rng) = *board;
+    let Some(mut saved) = SavedGame::capture(&session, &score, rng, board) else {
+        return;
+    };
+    saved.tick = tick.0;
+    if let Some(recorder) = recorder {
+        saved.actions = recorder.actions().to_vec();
+    }
+
+    SaveFile::write(&root, Some(&saved), &campaign);
+    slot.0 = Some(saved);
+}
+
+fn delete_save(
+    session: Res<GameSession>,
+    mut slot: ResMut<ContinueSlot>,
+    campaign: Res<CampaignProgress>,
+    root: Res<StorageRoot>,
+) {
+    // Поединок и гонка не сохранялись, и отложенная одиночная партия остаётся.
+    if session.mode.is_duel() {
+        return;
+    }
+    slot.0 = None;
+
+    SaveFile::write(&root, None, &campaign);
+}
+
+fn save_campaign_progress(
+    slot: Res<ContinueSlot>,
+    campaign: Res<CampaignProgress>,
+    root: Res<StorageRoot>,
+) {
+    SaveFile::write(&root, slot.0.as_ref(), &campaign);
+}
+
+/// Продолжает сохранённую партию: восстанавливает сессию и счёт, а доску построит `setup`.
+pub fn continue_game(
+    mut commands: Commands,
+    slot: Res<ContinueSlot>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(saved) = slot.0.clone() else {
+        return;
+    };
+
+    commands.insert_resource(saved.session());
+    commands.insert_resource(saved.score());
+    commands.insert_resource(saved);
+    next_state.set(AppState::InGame);
+}
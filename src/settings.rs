@@ -0,0 +1,233 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, WindowMode},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    accessibility::AccessibilitySettings,
+    audio::AudioSettings,
+    locale::Language,
+    storage::{self, StorageRoot},
+    theme::DEFAULT_THEME,
+};
+
+const SETTINGS_FILE: &str = "settings.toml";
+/// Множители скорости анимации, которые по кругу перебирает кнопка в настройках.
+const ANIMATION_SPEEDS: [f32; 5] = [0.5, 0.75, 1., 1.5, 2.];
+/// Задержки подсказки в секундах. 0 выключает её: подсказка появляется только по H.
+const HINT_DELAYS: [f32; 5] = [0., 3., 5., 10., 20.];
+
+/// Настройки игрока в `settings.toml` из каталога настроек.
+///
+/// Файл читается при сборке приложения, поэтому к первому `setup` настройки уже на месте.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let root = app
+            .init_resource::<StorageRoot>()
+            .world()
+            .resource::<StorageRoot>();
+        let settings = Settings::load(root);
+
+        app.insert_resource(settings)
+            .add_systems(Update, apply_window.run_if(resource_changed::<Settings>));
+    }
+}
+
+/// Все настройки игрока. Чего нет в файле, берётся по умолчанию, поэтому старые файлы читаются.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    /// Название темы из `assets/themes`.
+    pub theme: String,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// Множитель скорости фишек и эффектов. Партия запоминает его при старте, см. `GameSession`.
+    pub animation_speed: f32,
+    /// Через сколько секунд покоя доски показать подсказку, 0 — только по H.
+    pub hint_delay_secs: f32,
+    pub effects: bool,
+    pub audio: AudioSettings,
+    pub accessibility: AccessibilitySettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: Language::default(),
+            theme: DEFAULT_THEME.to_string(),
+            fullscreen: false,
+            vsync: true,
+            animation_speed: 1.,
+            hint_delay_secs: 0.,
+            effects: true,
+            audio: AudioSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    fn path(root: &StorageRoot) -> Option<PathBuf> {
+        root.config_path(SETTINGS_FILE)
+    }
+
+    /// Загружает настройки. Без файла или с испорченным файлом берутся значения по умолчанию.
+    pub fn load(root: &StorageRoot) -> Self {
+        let Some(path) = Self::path(root) else {
+            return Self::default();
+        };
+
+        match storage::read_toml::<Self>(&path) {
+            Ok(settings) => settings.sanitized(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Self::import_legacy(root).unwrap_or_default()
+            }
+            Err(err) => {
+                warn!("Failed to read settings from {}: {err}", path.display());
+                storage::back_up(&path);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, root: &StorageRoot) -> io::Result<()> {
+        let path = Self::path(root).ok_or_else(|| io::Error::other("No config directory"))?;
+
+        storage::write_toml(&path, self)
+    }
+
+    /// Переносит настройки из отдельных файлов прошлых версий и сразу пишет `settings.toml`,
+    /// поэтому старые файлы читаются только один раз.
+    fn import_legacy(root: &StorageRoot) -> Option<Self> {
+        let settings = Self::from_legacy_files(root.data_dir()?)?;
+        info!("Imported settings from previous version files");
+
+        if let Err(err) = settings.save(root) {
+            warn!("Failed to save imported settings: {err}");
+        }
+
+        Some(settings)
+    }
+
+    /// Настройки из файлов, которые прошлые версии хранили в каталоге данных по одному на раздел.
+    /// `None`, если ни одного такого файла нет.
+    pub fn from_legacy_files(dir: &Path) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Effects {
+            enabled: bool,
+        }
+        #[derive(Deserialize)]
+        struct Theme {
+            theme: String,
+        }
+        #[derive(Deserialize)]
+        struct Locale {
+            language: Language,
+        }
+
+        let mut settings = Self::default();
+        let mut found = false;
+
+        if let Some(audio) = read_legacy(dir, "audio.ron") {
+            settings.audio = audio;
+            found = true;
+        }
+        if let Some(effects) = read_legacy::<Effects>(dir, "effects.ron") {
+            settings.effects = effects.enabled;
+            found = true;
+        }
+        if let Some(theme) = read_legacy::<Theme>(dir, "theme.ron") {
+            settings.theme = theme.theme;
+            found = true;
+        }
+        if let Some(accessibility) = read_legacy(dir, "accessibility.ron") {
+            settings.accessibility = accessibility;
+            found = true;
+        }
+        if let Some(locale) = read_legacy::<Locale>(dir, "locale.ron") {
+            settings.language = locale.language;
+            found = true;
+        }
+
+        found.then_some(settings)
+    }
+
+    /// Значения, поправленные руками в файле, возвращаются в допустимые пределы.
+    pub fn sanitized(mut self) -> Self {
+        let (min_speed, max_speed) = (
+            ANIMATION_SPEEDS[0],
+            ANIMATION_SPEEDS[ANIMATION_SPEEDS.len() - 1],
+        );
+        if !(min_speed..=max_speed).contains(&self.animation_speed) {
+            warn!(
+                "Animation speed {} is out of {min_speed}..={max_speed}",
+                self.animation_speed
+            );
+            self.animation_speed = Self::default().animation_speed;
+        }
+        self.hint_delay_secs = self.hint_delay_secs.max(0.);
+
+        self
+    }
+
+    pub fn next_animation_speed(&mut self) {
+        self.animation_speed = next_value(&ANIMATION_SPEEDS, self.animation_speed);
+    }
+
+    pub fn next_hint_delay(&mut self) {
+        self.hint_delay_secs = next_value(&HINT_DELAYS, self.hint_delay_secs);
+    }
+}
+
+/// Испорченный старый файл пропускается: его раздел остаётся по умолчанию.
+fn read_legacy<T: DeserializeOwned>(dir: &Path, file: &str) -> Option<T> {
+    let path = dir.join(file);
+
+    match storage::read_ron(&path) {
+        Ok(value) => Some(value),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => {
+            warn!("Failed to import settings from {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Следующее значение по кругу. Значение не из списка, например из файла, сменяется первым.
+fn next_value(values: &[f32], current: f32) -> f32 {
+    values
+        .iter()
+        .position(|value| (value - current).abs() < f32::EPSILON)
+        .map_or(values[0], |idx| values[(idx + 1) % values.len()])
+}
+
+fn apply_window(settings: Res<Settings>, windows: Query<&mut Window>) {
+    let mode = if settings.fullscreen {
+        WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+    } else {
+        WindowMode::Windowed
+    };
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+
+    for mut window in windows {
+        if window.mode != mode {
+            window.mode = mode;
+        }
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+}
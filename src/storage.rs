@@ -0,0 +1,124 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::io::Reader, ecs::resource::Resource, log::warn};
+use ron::ser::PrettyConfig;
+use serde::{Serialize, de::DeserializeOwned};
+
+const APP_DIR: &str = "tile-matching";
+
+/// Каталог данных игры, например `~/.local/share/tile-matching`.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR))
+}
+
+/// Путь к файлу в каталоге данных игры, например `~/.local/share/tile-matching/<file>`.
+pub fn data_path(file: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(file))
+}
+
+/// Каталоги, где лежат файлы игрока: сохранение, рекорды, повторы и настройки.
+///
+/// Плагины читают файлы при сборке, поэтому свой каталог, например временный в тестах,
+/// вставляют до них.
+#[derive(Resource, Clone, Debug)]
+pub struct StorageRoot {
+    data: Option<PathBuf>,
+    config: Option<PathBuf>,
+}
+
+impl Default for StorageRoot {
+    /// Системные каталоги, например `~/.local/share/tile-matching` и `~/.config/tile-matching`.
+    fn default() -> Self {
+        Self {
+            data: data_dir(),
+            config: dirs::config_dir().map(|dir| dir.join(APP_DIR)),
+        }
+    }
+}
+
+impl StorageRoot {
+    /// Данные и настройки в одном каталоге.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+
+        Self {
+            data: Some(dir.clone()),
+            config: Some(dir),
+        }
+    }
+
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.data.as_deref()
+    }
+
+    pub fn data_path(&self, file: &str) -> Option<PathBuf> {
+        self.data.as_ref().map(|dir| dir.join(file))
+    }
+
+    pub fn config_path(&self, file: &str) -> Option<PathBuf> {
+        self.config.as_ref().map(|dir| dir.join(file))
+    }
+}
+
+pub fn read_ron<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let content = fs::read_to_string(path)?;
+
+    ron::from_str(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Читает RON-ассет. Ошибка разбора, как и в `read_ron`, отдаётся с видом `InvalidData`.
+pub async fn read_ron_asset<T: DeserializeOwned>(reader: &mut dyn Reader) -> io::Result<T> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+
+    ron::de::from_bytes(&bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_ron<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let content =
+        ron::ser::to_string_pretty(value, PrettyConfig::default()).map_err(io::Error::other)?;
+
+    write_atomically(path, &content)
+}
+
+pub fn read_toml<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let content = fs::read_to_string(path)?;
+
+    toml::from_str(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_toml<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let content = toml::to_string_pretty(value).map_err(io::Error::other)?;
+
+    write_atomically(path, &content)
+}
+
+/// Пишет файл через временный, чтобы падение посреди записи не портило старые данные.
+fn write_atomically(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+
+    fs::write(&tmp_path, content)?;
+    fs::rename(tmp_path, path)
+}
+
+/// Откладывает испорченный файл в сторону, чтобы его можно было разобрать вручную.
+pub fn back_up(path: &Path) {
+    let backup_path = path.with_extension("bak");
+
+    if let Err(err) = fs::rename(path, &backup_path) {
+        warn!("Failed to back up {}: {err}", path.display());
+    } else {
+        warn!(
+            "Moved unreadable {} to {}",
+            path.display(),
+            backup_path.display()
+        );
+    }
+}
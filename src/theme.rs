@@ -0,0 +1,319 @@
+use std::io;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedFolder, RecursiveDependencyLoadState, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    GameState,
+    board::Form,
+    locale::Locale,
+    replay::Playback,
+    settings::Settings,
+    storage::{self, StorageRoot},
+};
+
+const THEMES_FOLDER: &str = "themes";
+pub(crate) const DEFAULT_THEME: &str = "Default";
+
+/// Темы из `assets/themes`: цвета, формы или картинки фишек и шрифт.
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .init_resource::<ActiveTheme>()
+            .add_systems(Startup, load_themes)
+            .add_systems(
+                Update,
+                (
+                    collect_themes.run_if(not(resource_exists::<Themes>)),
+                    switch_theme_by_key
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(not(resource_exists::<Playback>)),
+                    apply_clear_color.run_if(resource_changed::<ActiveTheme>),
+                    apply_font,
+                ),
+            );
+    }
+}
+
+/// Цвет в виде `"#rrggbb"` или `"#rrggbbaa"`.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(try_from = "String")]
+pub struct HexColor(pub Color);
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(hex: String) -> Result<Self, Self::Error> {
+        Srgba::hex(&hex)
+            .map(|color| HexColor(color.into()))
+            .map_err(|err| format!("Invalid colour {hex:?}: {err}"))
+    }
+}
+
+/// Меш, которым рисуется фишка.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shape {
+    Circle,
+    Square,
+    Triangle,
+    Rhombus,
+    Annulus,
+    Hexagon,
+}
+
+impl Shape {
+    /// Меш в клетке размером 1 на 1.
+    pub fn mesh(&self) -> Mesh {
+        match self {
+            Shape::Circle => Circle::new(0.4).into(),
+            Shape::Square => Rectangle::from_size(Vec2::splat(0.8)).into(),
+            Shape::Triangle => Triangle2d::new(
+                Vec2::new(0., 0.4),
+                Vec2::new(-0.4, -0.4),
+                Vec2::new(0.4, -0.4),
+            )
+            .into(),
+            Shape::Rhombus => Rhombus::new(0.8, 0.8).into(),
+            Shape::Annulus => Annulus::new(0.3, 0.4).into(),
+            Shape::Hexagon => RegularPolygon::new(0.42, 6).into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum FormLook {
+    Shape(Shape),
+    /// Путь к картинке от каталога `assets`. Цвет формы её подкрашивает.
+    Sprite(String),
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FormStyle {
+    pub color: HexColor,
+    pub look: FormLook,
+    #[serde(skip)]
+    pub image: Option<Handle<Image>>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FormStyles {
+    pub circle: FormStyle,
+    pub square: FormStyle,
+    pub triangle: FormStyle,
+    pub rhombus: FormStyle,
+    pub annulus: FormStyle,
+}
+
+impl FormStyles {
+    pub fn get(&self, form: Form) -> &FormStyle {
+        match form {
+            Form::Circle => &self.circle,
+            Form::Square => &self.square,
+            Form::Triangle => &self.triangle,
+            Form::Rhombus => &self.rhombus,
+            Form::Annulus => &self.annulus,
+        }
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut FormStyle> {
+        [
+            &mut self.circle,
+            &mut self.square,
+            &mut self.triangle,
+            &mut self.rhombus,
+            &mut self.annulus,
+        ]
+        .into_iter()
+    }
+}
+
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub background: HexColor,
+    pub cell: HexColor,
+    pub selection: HexColor,
+    /// Путь к шрифту от каталога `assets`, без него берётся встроенный шрифт Bevy.
+    #[serde(default)]
+    pub font: Option<String>,
+    pub forms: FormStyles,
+    #[serde(skip)]
+    pub font_handle: Option<Handle<Font>>,
+}
+
+/// Встроенная тема, та же, что `assets/themes/default.theme.ron`. Нужна, пока темы не загрузились, и в тестах без ассетов.
+impl Default for Theme {
+    fn default() -> Self {
+        let style = |color: Color, shape| FormStyle {
+            color: HexColor(color),
+            look: FormLook::Shape(shape),
+            image: None,
+        };
+
+        Self {
+            name: DEFAULT_THEME.to_string(),
+            background: HexColor(Color::srgb_u8(43, 44, 47)),
+            cell: HexColor(Color::srgb_u8(31, 31, 46)),
+            selection: HexColor(Color::srgb_u8(165, 187, 192)),
+            font: None,
+            forms: FormStyles {
+                circle: style(Color::srgb_u8(175, 43, 30), Shape::Circle),
+                square: style(Color::srgb_u8(71, 132, 48), Shape::Square),
+                triangle: style(Color::srgb_u8(27, 85, 131), Shape::Triangle),
+                rhombus: style(Color::srgb_u8(229, 132, 38), Shape::Rhombus),
+                annulus: style(Color::srgb_u8(217, 119, 169), Shape::Annulus),
+            },
+            font_handle: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    type Asset = Theme;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Theme, Self::Error> {
+        let mut theme: Theme = storage::read_ron_asset(reader).await?;
+        for style in theme.forms.iter_mut() {
+            if let FormLook::Sprite(path) = &style.look {
+                style.image = Some(load_context.load(path.clone()));
+            }
+        }
+        theme.font_handle = theme.font.clone().map(|path| load_context.load(path));
+
+        Ok(theme)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+/// Тема, которой сейчас рисуется игра.
+#[derive(Resource, Default)]
+pub struct ActiveTheme(pub Theme);
+
+/// Все загруженные темы по имени и номер текущей.
+#[derive(Resource)]
+pub struct Themes {
+    handles: Vec<Handle<Theme>>,
+    current: usize,
+}
+
+#[derive(Resource)]
+struct ThemeFolder(Handle<LoadedFolder>);
+
+fn load_themes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThemeFolder(asset_server.load_folder(THEMES_FOLDER)));
+}
+
+/// Ждёт, пока загрузится каталог тем, и включает тему из настроек. Испорченные темы пропускаются.
+fn collect_themes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    folder: Res<ThemeFolder>,
+    folders: Res<Assets<LoadedFolder>>,
+    themes: Res<Assets<Theme>>,
+    settings: Res<Settings>,
+    mut active: ResMut<ActiveTheme>,
+) {
+    match asset_server.recursive_dependency_load_state(&folder.0) {
+        RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed(_) => {}
+        _ => return,
+    }
+    let Some(folder) = folders.get(&folder.0) else {
+        return;
+    };
+
+    let mut handles: Vec<Handle<Theme>> = folder
+        .handles
+        .iter()
+        .filter_map(|handle| handle.clone().try_typed::<Theme>().ok())
+        .filter(|handle| themes.contains(handle))
+        .collect();
+    handles.sort_by_key(|handle| themes.get(handle).map(|theme| theme.name.clone()));
+
+    let current = handles
+        .iter()
+        .position(|handle| {
+            themes
+                .get(handle)
+                .is_some_and(|theme| theme.name == settings.theme)
+        })
+        .unwrap_or_default();
+    if let Some(theme) = handles.get(current).and_then(|handle| themes.get(handle)) {
+        active.0 = theme.clone();
+    }
+
+    commands.insert_resource(Themes { handles, current });
+}
+
+/// Включает следующую тему по кругу и запоминает выбор.
+pub(crate) fn next_theme(
+    themes: Option<ResMut<Themes>>,
+    assets: Res<Assets<Theme>>,
+    mut active: ResMut<ActiveTheme>,
+    mut settings: ResMut<Settings>,
+    root: Res<StorageRoot>,
+) {
+    let Some(mut themes) = themes.filter(|themes| !themes.handles.is_empty()) else {
+        return;
+    };
+
+    themes.current = (themes.current + 1) % themes.handles.len();
+    let Some(theme) = assets.get(&themes.handles[themes.current]) else {
+        return;
+    };
+
+    active.0 = theme.clone();
+    settings.theme.clone_from(&theme.name);
+    if let Err(err) = settings.save(&root) {
+        warn!("Failed to save settings: {err}");
+    }
+}
+
+fn switch_theme_by_key(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        commands.run_system_cached(next_theme);
+    }
+}
+
+fn apply_clear_color(theme: Res<ActiveTheme>, mut clear_color: ResMut<ClearColor>) {
+    clear_color.0 = theme.0.background.0;
+}
+
+/// Шрифт темы или языка получают и уже показанные надписи, и те, что появятся позже.
+fn apply_font(
+    theme: Res<ActiveTheme>,
+    locale: Res<Locale>,
+    asset_server: Res<AssetServer>,
+    texts: Query<&mut TextFont>,
+) {
+    let font = match (&theme.0.font_handle, locale.language().font()) {
+        (Some(font), _) => font.clone(),
+        (None, Some(path)) => asset_server.load(path),
+        (None, None) => Handle::default(),
+    };
+    let changed = theme.is_changed() || locale.is_changed();
+
+    for mut text_font in texts {
+        if (changed || text_font.is_added()) && text_font.font != font {
+            text_font.font = font.clone();
+        }
+    }
+}
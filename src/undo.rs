@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    BoardAssets, GameRng, GameSession, GameState, GameTick, GameplaySystems, ScoreStorage,
+    Selection,
+    board::Board,
+    board_settled, rebuild_tiles,
+    replay::{Playback, ReplayRecorder},
+    savegame::{SavedGame, autosave},
+};
+
+const HISTORY_LIMIT: usize = 20;
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        // Саму историю заводит `GameplayPlugin`: в неё пишет `check_swapped_for_matching`.
+        app.add_systems(
+            Update,
+            request_undo
+                .run_if(in_state(GameState::Playing))
+                .run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (remember_settled, undo)
+                .chain()
+                .after(GameplaySystems)
+                .run_if(in_state(GameState::Playing))
+                .run_if(board_settled),
+        );
+    }
+}
+
+/// Снимки успокоившейся доски перед каждым принятым обменом.
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    snapshots: VecDeque<SavedGame>,
+    /// Последнее успокоившееся состояние, ещё не попавшее в историю.
+    settled: Option<SavedGame>,
+    /// Отмена запрошена и выполнится на первом тике, когда доска успокоится.
+    requested: bool,
+}
+
+impl UndoHistory {
+    /// Кладёт в историю состояние, из которого был сделан принятый обмен.
+    ///
+    /// Если обмен сделан посреди каскада, снимок уже забран предыдущим обменом,
+    /// и отмена вернёт доску сразу к состоянию перед ними обоими.
+    pub fn push_settled(&mut self) {
+        let Some(snapshot) = self.settled.take() else {
+            return;
+        };
+
+        if self.snapshots.len() == HISTORY_LIMIT {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+}
+
+fn request_undo(
+    keys: Res<ButtonInput<KeyCode>>,
+    session: Res<GameSession>,
+    mut history: ResMut<UndoHistory>,
+) {
+    let ctrl_pressed = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if ctrl_pressed && keys.just_pressed(KeyCode::KeyZ) && session.undos_left != Some(0) {
+        history.request();
+    }
+}
+
+fn remember_settled(
+    mut history: ResMut<UndoHistory>,
+    session: Res<GameSession>,
+    score: Res<ScoreStorage>,
+    board: Single<(&Board, &GameRng)>,
+) {
+    let (board, rng) = *board;
+    if history.settled.is_none() {
+        history.settled = SavedGame::capture(&session, &score, rng, board);
+    }
+}
+
+fn undo(
+    mut commands: Commands,
+    mut history: ResMut<UndoHistory>,
+    mut session: ResMut<GameSession>,
+    mut score: ResMut<ScoreStorage>,
+    board: Single<(Entity, &mut Board, &mut GameRng, &mut Selection)>,
+    board_assets: Res<BoardAssets>,
+    tick: Res<GameTick>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+) {
+    if !history.requested {
+        return;
+    }
+    history.requested = false;
+    let (board_entity, mut board, mut rng, mut selection) = board.into_inner();
+
+    if session.undos_left == Some(0) {
+        return;
+    }
+
+    let Some(snapshot) = history.snapshots.pop_back() else {
+        return;
+    };
+
+    if let Some(undos_left) = session.undos_left.as_mut() {
+        *undos_left -= 1;
+    }
+
+    snapshot.rewind(&mut session, &mut score, &mut rng);
+    rebuild_tiles(
+        &mut commands,
+        board_entity,
+        &mut board,
+        &board_assets,
+        &snapshot.board,
+    );
+    *selection = Selection::default();
+    history.settled = Some(snapshot);
+
+    if let Some(mut recorder) = recorder {
+        recorder.record_undo(tick.0);
+    }
+
+    commands.run_system_cached(autosave);
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use crate::{GameSession, messages::MatchFound};
+
+/// Сколько раундов длится поединок. За раунд каждый игрок ходит хотя бы раз.
+pub const VERSUS_ROUNDS: u32 = 10;
+/// Совпадение хотя бы из стольких фишек даёт ещё один ход.
+const EXTRA_TURN_TILES: usize = 4;
+
+/// Игрок. На доске это её хозяин: в гонке у каждого своя доска, в остальных режимах доска одна у первого.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Player {
+    #[default]
+    One,
+    Two,
+}
+
+impl Player {
+    pub const ALL: [Player; 2] = [Player::One, Player::Two];
+
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            Player::One => "player-one",
+            Player::Two => "player-two",
+        }
+    }
+
+    pub fn other(&self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+}
+
+/// Поединок вдвоём за одной доской. Игроки ходят по очереди, ход кончается, когда доска успокоилась,
+/// поэтому очки за каскад достаются тому, кто его начал.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Versus {
+    player: Player,
+    round: u32,
+    /// Обмен сделан, и доска ещё не успокоилась после него.
+    turn_taken: bool,
+    /// За этот ход было большое совпадение.
+    extra_turn: bool,
+}
+
+impl Default for Versus {
+    fn default() -> Self {
+        Self {
+            player: Player::One,
+            round: 1,
+            turn_taken: false,
+            extra_turn: false,
+        }
+    }
+}
+
+impl Versus {
+    /// Чей сейчас ход.
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Номер раунда с единицы. После последнего раунда он на один больше `VERSUS_ROUNDS`.
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.round > VERSUS_ROUNDS
+    }
+
+    /// Пока доска не успокоилась после хода, следующий обмен сделать нельзя.
+    pub fn turn_in_progress(&self) -> bool {
+        self.turn_taken
+    }
+
+    pub(crate) fn take_turn(&mut self) {
+        self.turn_taken = true;
+    }
+
+    /// Обмен без совпадений не считается ходом, игрок пробует ещё раз.
+    pub(crate) fn cancel_turn(&mut self) {
+        self.turn_taken = false;
+    }
+
+    fn end_turn(&mut self) {
+        self.turn_taken = false;
+        if std::mem::take(&mut self.extra_turn) {
+            return;
+        }
+
+        if self.player == Player::Two {
+            self.round += 1;
+        }
+        self.player = self.player.other();
+    }
+}
+
+/// Запоминает большое совпадение за ход, хоть от самого обмена, хоть от каскада.
+/// Каскады стартовой доски случаются до первого хода и ничего не дают.
+pub(crate) fn grant_extra_turn(
+    mut session: ResMut<GameSession>,
+    mut matches: MessageReader<MatchFound>,
+) {
+    let big_match = matches
+        .read()
+        .any(|found| found.cells.len() >= EXTRA_TURN_TILES);
+
+    if big_match && let Some(versus) = session.versus.as_mut().filter(|versus| versus.turn_taken) {
+        versus.extra_turn = true;
+    }
+}
+
+pub(crate) fn end_turn(mut session: ResMut<GameSession>) {
+    if let Some(versus) = session.versus.as_mut().filter(|versus| versus.turn_taken) {
+        versus.end_turn();
+    }
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use bevy::prelude::*;
+use tile_matching::{
+    accessibility::{
+        AccessibilityPlugin, AccessibilitySettings, Announcement, KeyboardCursor, Palette,
+    },
+    board::{Form, Tile},
+    settings::Settings,
+};
+
+use common::{app, app_with, board, load_board, pattern_board, settle};
+
+fn reader_app() -> App {
+    app_with(|app| {
+        app.add_plugins(AccessibilityPlugin)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(Settings {
+                accessibility: AccessibilitySettings {
+                    screen_reader: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+    })
+}
+
+/// Нажимает и отпускает клавишу за один `update`. Без `InputPlugin` нажатие само не сбрасывается.
+fn press(app: &mut App, key: KeyCode) -> String {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+    app.update();
+
+    let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keys.release(key);
+    keys.clear();
+
+    app.world().resource::<Announcement>().0.clone()
+}
+
+fn tiles(app: &App) -> Vec<Tile> {
+    board(app)
+        .into_iter()
+        .flatten()
+        .filter_map(|cell| cell.tile)
+        .collect()
+}
+
+fn tile_color(app: &App, tile: &Tile) -> Color {
+    let world = app.world();
+    let material = world
+        .get::<MeshMaterial2d<ColorMaterial>>(tile.visual_entity)
+        .unwrap();
+
+    world
+        .resource::<Assets<ColorMaterial>>()
+        .get(&material.0)
+        .unwrap()
+        .color
+}
+
+fn glyph_visibility(app: &App, tile: &Tile) -> Visibility {
+    let world = app.world();
+    let glyph = world.get::<Children>(tile.visual_entity).unwrap()[0];
+
+    *world.get::<Visibility>(glyph).unwrap()
+}
+
+#[test]
+fn palettes_keep_forms_distinct() {
+    for palette in Palette::ALL.into_iter().skip(1) {
+        let colors = Form::ALL.map(|form| palette.color(form).unwrap().to_srgba());
+
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert_ne!(a, b, "{palette:?} repeats a colour");
+            }
+        }
+    }
+    assert!(Palette::Theme.color(Form::Circle).is_none());
+}
+
+#[test]
+fn palette_and_glyphs_apply_live() {
+    let mut app = app();
+    let tile = tiles(&app)[0];
+    assert_eq!(glyph_visibility(&app, &tile), Visibility::Hidden);
+
+    app.world_mut().resource_mut::<Settings>().accessibility = AccessibilitySettings {
+        palette: Palette::Tritanopia,
+        glyphs: true,
+        ..Default::default()
+    };
+    app.update();
+
+    for tile in tiles(&app) {
+        assert_eq!(
+            tile_color(&app, &tile),
+            Palette::Tritanopia.color(tile.form).unwrap()
+        );
+        assert_eq!(glyph_visibility(&app, &tile), Visibility::Inherited);
+    }
+}
+
+#[test]
+fn volume_change_does_not_restyle_board() {
+    let mut app = app();
+    let forms = pattern_board(&app);
+    load_board(&mut app, forms);
+    settle(&mut app);
+    let tile = tiles(&app)[0];
+    let material = |app: &App| {
+        app.world()
+            .get::<MeshMaterial2d<ColorMaterial>>(tile.visual_entity)
+            .unwrap()
+            .0
+            .id()
+    };
+    let before = material(&app);
+
+    app.world_mut().resource_mut::<Settings>().audio.music = 0.1;
+    app.update();
+
+    assert_eq!(material(&app), before);
+}
+
+#[test]
+fn cursor_announces_cells_and_stays_on_board() {
+    let mut app = reader_app();
+    let mut forms = pattern_board(&app);
+    forms[0][1] = Form::Rhombus;
+    load_board(&mut app, forms);
+
+    assert_eq!(press(&mut app, KeyCode::ArrowUp), "Row 1, column 1, circle");
+    assert_eq!(
+        press(&mut app, KeyCode::ArrowRight),
+        "Row 1, column 2, rhombus"
+    );
+    press(&mut app, KeyCode::ArrowDown);
+    press(&mut app, KeyCode::ArrowLeft);
+    press(&mut app, KeyCode::ArrowLeft);
+
+    assert_eq!(
+        app.world().resource::<KeyboardCursor>().0,
+        Some((0, 0).into())
+    );
+}
+
+#[test]
+fn keyboard_swap_announces_match_and_score() {
+    let mut app = reader_app();
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(&mut app, forms);
+
+    press(&mut app, KeyCode::ArrowRight);
+    press(&mut app, KeyCode::ArrowRight);
+    press(&mut app, KeyCode::ArrowRight);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::ArrowRight);
+    press(&mut app, KeyCode::Enter);
+
+    let mut heard = Vec::new();
+    for _ in 0..120 {
+        app.update();
+        let announcement = &app.world().resource::<Announcement>().0;
+        if heard.last() != Some(announcement) {
+            heard.push(announcement.clone());
+        }
+    }
+    settle(&mut app);
+
+    let heard = heard.join(". ");
+    assert!(heard.contains("Matched 3 circles"), "{heard}");
+    assert!(heard.contains("30 points, score 30"), "{heard}");
+}
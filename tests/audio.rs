@@ -0,0 +1,144 @@
+mod common;
+
+use bevy::{asset::AssetPlugin, audio::Volume, prelude::*};
+use tile_matching::{
+    GameState,
+    audio::{AudioSettings, GameAudioPlugin, SoundEffect, VolumeChannel},
+    autoplay::AutoplayPlugin,
+    board::Form,
+    daily::DailyRecords,
+    highscores::HighScores,
+    messages::CursorMoved,
+    settings::Settings,
+};
+
+use common::{app_with, autoplay, load_board, pattern_board, score, settle, swap};
+
+const SETTINGS: AudioSettings = AudioSettings {
+    master: 0.5,
+    music: 0.3,
+    effects: 0.8,
+};
+
+/// Без `AudioPlugin` из Bevy: звуки не загружаются и не играют, но сущности для них появляются.
+fn app() -> App {
+    app_with(|app| {
+        app.add_plugins(AssetPlugin::default())
+            .init_asset::<AudioSource>()
+            .add_plugins(GameAudioPlugin)
+            .insert_resource(Settings {
+                audio: SETTINGS,
+                ..Default::default()
+            });
+    })
+}
+
+fn played_effects(app: &mut App) -> Vec<PlaybackSettings> {
+    let world = app.world_mut();
+
+    world
+        .query_filtered::<&PlaybackSettings, With<SoundEffect>>()
+        .iter(world)
+        .copied()
+        .collect()
+}
+
+#[test]
+fn matching_swap_plays_swap_and_match() {
+    let mut app = app();
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(&mut app, forms);
+    settle(&mut app);
+
+    swap(&mut app, (0, 2), (0, 3));
+    settle(&mut app);
+
+    let effects = played_effects(&mut app);
+    assert_eq!(effects.len(), 2);
+    for effect in effects {
+        assert_eq!(effect.speed, 1.);
+        assert_eq!(effect.volume, Volume::Linear(0.4));
+    }
+}
+
+#[test]
+fn invalid_swap_plays_two_sounds() {
+    let mut app = app();
+    let forms = pattern_board(&app);
+    load_board(&mut app, forms);
+    settle(&mut app);
+
+    swap(&mut app, (5, 5), (5, 6));
+    settle(&mut app);
+
+    assert_eq!(played_effects(&mut app).len(), 2);
+}
+
+#[test]
+fn volume_steps_wrap_around() {
+    let mut settings = SETTINGS;
+
+    settings.step(VolumeChannel::Effects);
+    assert!((settings.effects - 0.9).abs() < 1e-6);
+    settings.step(VolumeChannel::Effects);
+    assert!((settings.effects - 1.).abs() < 1e-6);
+    settings.step(VolumeChannel::Effects);
+    assert_eq!(settings.effects, 0.);
+
+    assert_eq!(settings.master, SETTINGS.master);
+    assert_eq!(settings.music, SETTINGS.music);
+}
+
+#[test]
+fn cursor_plays_form_cue_only_when_enabled() {
+    let mut app = app();
+    let forms = pattern_board(&app);
+    load_board(&mut app, forms);
+    settle(&mut app);
+
+    for form_cues in [false, true] {
+        app.world_mut()
+            .resource_mut::<Settings>()
+            .accessibility
+            .form_cues = form_cues;
+        app.world_mut().write_message(CursorMoved {
+            cell: (0, 0).into(),
+            form: Some(Form::Square),
+        });
+        app.update();
+    }
+
+    assert_eq!(played_effects(&mut app).len(), 1);
+}
+
+#[test]
+fn autoplayed_game_ends_with_losing_sound() {
+    let mut app = app_with(|app| {
+        app.add_plugins(AssetPlugin::default())
+            .init_asset::<AudioSource>()
+            .add_plugins((GameAudioPlugin, AutoplayPlugin))
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<HighScores>()
+            .init_resource::<DailyRecords>();
+    });
+    autoplay(&mut app);
+    // С таким счётом партия попала бы в пустую таблицу рекордов.
+    assert!(score(&app) > 0);
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::GameOver);
+    app.update();
+
+    let world = app.world_mut();
+    let sounds: Vec<_> = world
+        .query_filtered::<&AudioPlayer, With<SoundEffect>>()
+        .iter(world)
+        .filter_map(|player| player.0.path().map(|path| path.to_string()))
+        .collect();
+    assert!(sounds.iter().any(|path| path == "sounds/lose.wav"));
+    assert!(!sounds.iter().any(|path| path == "sounds/win.wav"));
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use std::{collections::HashSet, fs, path::Path};
+
+use bevy::{asset::AssetPlugin, prelude::*};
+use tile_matching::{
+    GameMode, GameSession, GameState,
+    autoplay::AutoplayPlugin,
+    board::Form,
+    campaign::{Campaign, CampaignPlugin, CampaignProgress},
+    level::{Level, LevelGoal},
+};
+
+use common::{app_with, autoplay, load_board, pattern_board, settle, swap};
+
+fn campaign() -> Campaign {
+    let manifest = fs::read_to_string("assets/levels/main.campaign.ron").unwrap();
+
+    ron::from_str(&manifest).unwrap()
+}
+
+fn levels(campaign: &Campaign) -> Vec<Level> {
+    campaign
+        .chapters
+        .iter()
+        .flat_map(|chapter| &chapter.levels)
+        .map(|path| Level::load(&Path::new("assets").join(path)).unwrap())
+        .collect()
+}
+
+#[test]
+fn manifest_references_playable_levels() {
+    let campaign = campaign();
+    let levels = levels(&campaign);
+
+    assert!(
+        campaign
+            .chapters
+            .iter()
+            .all(|chapter| !chapter.levels.is_empty())
+    );
+    for level in &levels {
+        assert!(!level.goals.is_empty(), "{} has no goals", level.name);
+        assert!(level.moves.is_some(), "{} has no move limit", level.name);
+    }
+
+    // По имени уровня ведутся рекорды, поэтому имена не должны повторяться.
+    let names = levels
+        .iter()
+        .map(|level| &level.name)
+        .collect::<HashSet<_>>();
+    assert_eq!(names.len(), levels.len());
+}
+
+#[test]
+fn levels_unlock_in_order() {
+    let names = ["one", "two", "three"];
+    let mut progress = CampaignProgress::default();
+    assert_eq!(progress.unlocked(names), 1);
+
+    assert!(progress.record("one", 1));
+    assert_eq!(progress.unlocked(names), 2);
+
+    // Пройденный вне очереди уровень не открывает тех, что за непройденным.
+    progress.record("three", 2);
+    assert_eq!(progress.unlocked(names), 2);
+
+    progress.record("two", 3);
+    assert_eq!(progress.unlocked(names), 4);
+}
+
+#[test]
+fn best_stars_are_kept() {
+    let mut progress = CampaignProgress::default();
+
+    assert!(progress.record("one", 2));
+    assert!(!progress.record("one", 1));
+    assert!(progress.record("one", 3));
+    assert_eq!(progress.stars("one"), 3);
+    assert_eq!(progress.stars("two"), 0);
+}
+
+#[test]
+fn stars_follow_score_thresholds() {
+    let level: Level = ron::from_str(
+        r#"(name: "Stars", layout: "C S", goals: [Score(100)], star_scores: [500, 900])"#,
+    )
+    .unwrap();
+
+    assert_eq!(level.max_stars(), 3);
+    assert_eq!(level.stars(100), 1);
+    assert_eq!(level.stars(500), 2);
+    assert_eq!(level.stars(1000), 3);
+}
+
+fn level_app() -> App {
+    let level = Level {
+        name: "Test".to_string(),
+        layout: "...".parse().unwrap(),
+        moves: Some(5),
+        goals: vec![LevelGoal::Score(1)],
+        star_scores: Vec::new(),
+    };
+
+    app_with(|app| {
+        app.add_plugins((AssetPlugin::default(), CampaignPlugin, AutoplayPlugin))
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(
+                GameSession::new(GameMode::Campaign, 1.)
+                    .with_level(level)
+                    .with_level_path("levels/test.level.ron".to_string()),
+            );
+    })
+}
+
+fn state(app: &App) -> GameState {
+    *app.world().resource::<State<GameState>>().get()
+}
+
+#[test]
+fn cleared_level_is_recorded_by_its_path() {
+    let mut app = level_app();
+    settle(&mut app);
+
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(&mut app, forms);
+    swap(&mut app, (0, 2), (0, 3));
+    settle(&mut app);
+
+    assert_eq!(state(&app), GameState::GameOver);
+    let progress = app.world().resource::<CampaignProgress>();
+    assert_eq!(progress.stars("levels/test.level.ron"), 1);
+    assert_eq!(progress.stars("Test"), 0);
+}
+
+#[test]
+fn level_cleared_by_autoplay_is_not_recorded() {
+    let mut app = level_app();
+    // Доска, на которой автоигре есть ход, а сама она не сыграет.
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(&mut app, forms);
+
+    autoplay(&mut app);
+
+    assert_eq!(state(&app), GameState::GameOver);
+    assert!(app.world().resource::<GameSession>().autoplayed());
+    let progress = app.world().resource::<CampaignProgress>();
+    assert_eq!(progress.stars("levels/test.level.ron"), 0);
+}
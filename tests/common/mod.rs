@@ -0,0 +1,176 @@
+//! Общая обвязка для тестов, которые гоняют игру в `App` без окна.
+
+// Каждый тест подключает модуль целиком, а пользуется только частью.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::{
+    ecs::system::RunSystemOnce, prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy,
+};
+use tile_matching::{
+    AppState, BoardAssets, CheckMatchesOrSwap, GameplayPlugin, Moving, ScoreStorage, Selection,
+    autoplay::Autoplay,
+    board::{Board, BoardIndex, Form},
+    rebuild_tiles,
+    versus::Player,
+};
+
+/// Один тик `FixedUpdate` по умолчанию, поэтому каждый `update` двигает доску ровно на тик.
+const TICK: Duration = Duration::from_micros(15_625);
+/// С запасом хватает, чтобы фишки проехали всю доску и каскады досчитались.
+const SETTLE_TICKS: usize = 600;
+
+pub fn app() -> App {
+    app_with(|_| {})
+}
+
+/// То же, что `app`, но `setup` может добавить плагины до первого `update`.
+pub fn app_with(setup: impl FnOnce(&mut App)) -> App {
+    let mut app = App::new();
+
+    app.add_plugins((MinimalPlugins, StatesPlugin, GameplayPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TICK))
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<Assets<ColorMaterial>>()
+        .init_resource::<ClearColor>();
+    setup(&mut app);
+
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::InGame);
+    app.update();
+
+    app
+}
+
+/// Форма клетки на доске без единого совпадения: соседние формы всегда разные.
+pub fn pattern(row_id: usize, col_id: usize) -> Form {
+    Form::ALL[(row_id + 2 * col_id) % Form::ALL.len()]
+}
+
+/// Доска игрока. Доски — сущности, поэтому её ищут запросом по хозяину.
+pub fn board_of(app: &App, player: Player) -> &Board {
+    let world = app.world();
+
+    world
+        .try_query::<(&Board, &Player)>()
+        .expect("Boards are not spawned")
+        .iter(world)
+        .find_map(|(board, owner)| (*owner == player).then_some(board))
+        .expect("Player has no board")
+}
+
+/// Доска первого игрока, единственная вне гонки.
+pub fn board(app: &App) -> &Board {
+    board_of(app, Player::One)
+}
+
+pub fn pattern_board(app: &App) -> Vec<Vec<Form>> {
+    let board = board(app);
+
+    (0..board.height())
+        .map(|row_id| {
+            (0..board.width())
+                .map(|col_id| pattern(row_id, col_id))
+                .collect()
+        })
+        .collect()
+}
+
+pub fn load_board(app: &mut App, forms: Vec<Vec<Form>>) {
+    load_board_of(app, Player::One, forms);
+}
+
+pub fn load_board_of(app: &mut App, player: Player, forms: Vec<Vec<Form>>) {
+    app.world_mut()
+        .run_system_once(
+            move |mut commands: Commands,
+                  boards: Query<(Entity, &mut Board, &Player)>,
+                  assets: Res<BoardAssets>| {
+                let (entity, mut board, _) = boards
+                    .into_iter()
+                    .find(|(_, _, owner)| **owner == player)
+                    .expect("Player has no board");
+                let forms: Vec<Vec<_>> = forms
+                    .iter()
+                    .map(|row| row.iter().copied().map(Some).collect())
+                    .collect();
+                rebuild_tiles(&mut commands, entity, &mut board, &assets, &forms);
+            },
+        )
+        .unwrap();
+    app.update();
+}
+
+pub fn swap(app: &mut App, from: (usize, usize), to: (usize, usize)) {
+    swap_on(app, Player::One, from, to);
+}
+
+pub fn swap_on(app: &mut App, player: Player, from: (usize, usize), to: (usize, usize)) {
+    let world = app.world_mut();
+    let (mut selection, _) = world
+        .query::<(&mut Selection, &Player)>()
+        .iter_mut(world)
+        .find(|(_, owner)| **owner == player)
+        .expect("Player has no board");
+
+    selection.request_swap(from.into(), to.into());
+}
+
+pub fn settle(app: &mut App) {
+    for _ in 0..SETTLE_TICKS {
+        app.update();
+    }
+}
+
+/// Даёт автоигре походить, пока тесту хватает тиков, и ждёт, пока доска успокоится. Нужен `AutoplayPlugin`.
+pub fn autoplay(app: &mut App) {
+    app.world_mut().resource_mut::<Autoplay>().enabled = true;
+    settle(app);
+    app.world_mut().resource_mut::<Autoplay>().enabled = false;
+    settle(app);
+}
+
+pub fn visible_forms(app: &App) -> Vec<Vec<Form>> {
+    let board = board(app);
+
+    (0..board.visible_height())
+        .map(|row_id| {
+            board[row_id]
+                .iter()
+                .map(|cell| cell.tile.expect("Settled board has no holes").form)
+                .collect()
+        })
+        .collect()
+}
+
+pub fn score(app: &App) -> usize {
+    app.world().resource::<ScoreStorage>().total()
+}
+
+/// Ничего не движется, каждая клетка каждой доски занята, а фишка стоит ровно в своей клетке.
+pub fn assert_settled(app: &mut App) {
+    let world = app.world_mut();
+
+    assert_eq!(world.query::<&Moving>().iter(world).count(), 0);
+    assert_eq!(world.query::<&CheckMatchesOrSwap>().iter(world).count(), 0);
+
+    let mut boards = world.query::<&Board>();
+    let world = &*world;
+    for board in boards.iter(world) {
+        for row_id in 0..board.height() {
+            for col_id in 0..board.width() {
+                let idx: BoardIndex = (row_id, col_id).into();
+                let tile = board[idx].tile.expect("Settled board has no holes");
+                let transform = world.get::<Transform>(tile.entity).unwrap();
+
+                assert_eq!(
+                    transform.translation.truncate(),
+                    board.get_cell_coord(idx),
+                    "Tile at ({row_id}, {col_id}) is out of place"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use std::{env, fs};
+
+use bevy::prelude::*;
+use chrono::{Days, Local, NaiveDate};
+use tile_matching::{
+    GameMode, GameSession, GameState,
+    autoplay::AutoplayPlugin,
+    board::Form,
+    daily::{DailyPlugin, DailyRecords, daily_seed},
+    storage::StorageRoot,
+};
+
+use common::{app_with, autoplay, board};
+
+fn day(text: &str) -> NaiveDate {
+    text.parse().unwrap()
+}
+
+fn forms(day: NaiveDate) -> Vec<Vec<Form>> {
+    let app = app_with(|app| {
+        app.insert_resource(GameSession::new(GameMode::Daily, 1.).with_day(day));
+    });
+
+    board(&app)
+        .into_iter()
+        .map(|row| row.iter().map(|cell| cell.tile.unwrap().form).collect())
+        .collect()
+}
+
+#[test]
+fn seed_does_not_change_between_versions() {
+    // Значение записано один раз: если оно поменялось, старые дни больше не сыграть.
+    assert_eq!(daily_seed(day("2024-01-01")), 0xb8a0_7cae_04a5_1ced);
+    assert_ne!(daily_seed(day("2024-01-01")), daily_seed(day("2024-01-02")));
+}
+
+#[test]
+fn same_day_gives_same_board() {
+    assert_eq!(forms(day("2026-03-14")), forms(day("2026-03-14")));
+    assert_ne!(forms(day("2026-03-14")), forms(day("2026-03-15")));
+}
+
+#[test]
+fn records_keep_best_score_per_day() {
+    let mut records = DailyRecords::default();
+
+    assert!(records.record(day("2026-05-01"), 0));
+    assert!(records.record(day("2026-05-01"), 300));
+    assert!(!records.record(day("2026-05-01"), 200));
+    assert!(records.record(day("2026-05-02"), 100));
+
+    assert_eq!(records.best(day("2026-05-01")), Some(300));
+    assert_eq!(records.best(day("2026-05-02")), Some(100));
+    assert_eq!(records.best(day("2026-05-03")), None);
+}
+
+#[test]
+fn streak_counts_consecutive_days() {
+    let mut records = DailyRecords::default();
+    for date in ["2026-05-01", "2026-05-03", "2026-05-04", "2026-05-05"] {
+        records.record(day(date), 100);
+    }
+
+    assert_eq!(records.streak(day("2026-05-05")), 3);
+    // Сегодня ещё не сыграно, серия до вчера цела.
+    assert_eq!(records.streak(day("2026-05-06")), 3);
+    assert_eq!(records.streak(day("2026-05-07")), 0);
+    assert_eq!(records.streak(day("2026-05-01")), 1);
+}
+
+#[test]
+fn only_daily_played_on_its_day_by_player_is_recorded() {
+    // Рекорды дней пишутся в каталог данных, поэтому тест подменяет его своим.
+    let dir = env::temp_dir().join(format!("tile-matching-daily-{}", std::process::id()));
+    let yesterday = Local::now().date_naive() - Days::new(1);
+
+    let on_its_day = || {
+        GameSession::new(GameMode::Daily, 1.)
+            .with_day(yesterday)
+            .with_start_day(yesterday)
+    };
+
+    let best = [
+        // За партию с автоигрой играл ИИ.
+        (on_its_day(), true),
+        // Вчерашнее испытание, начатое сегодня, только переигровка.
+        (
+            GameSession::new(GameMode::Daily, 1.).with_day(yesterday),
+            false,
+        ),
+        (on_its_day(), false),
+    ]
+    .map(|(session, autoplayed)| {
+        let mut app = app_with(|app| {
+            app.insert_resource(StorageRoot::new(&dir))
+                .add_plugins((DailyPlugin, AutoplayPlugin))
+                .init_resource::<ButtonInput<KeyCode>>()
+                .insert_resource(session);
+        });
+        if autoplayed {
+            autoplay(&mut app);
+            assert!(app.world().resource::<GameSession>().autoplayed());
+        }
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::GameOver);
+        app.update();
+
+        app.world().resource::<DailyRecords>().best(yesterday)
+    });
+    fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(best, [None, None, Some(0)]);
+}
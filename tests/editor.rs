@@ -0,0 +1,267 @@
+mod common;
+
+use bevy::prelude::*;
+use tile_matching::{
+    GameState, ScoreStorage,
+    board::{BoardLayout, CellKind, Form, LayoutCell},
+    editor::LevelEditor,
+    level::{Level, LevelGoal},
+    rules::SCORE_PER_TILE,
+};
+
+use common::{app_with, board, load_board, pattern, pattern_board, settle, swap};
+
+fn level(layout: &str) -> Level {
+    Level {
+        name: "Test".to_string(),
+        layout: layout.parse().unwrap(),
+        moves: None,
+        goals: Vec::new(),
+        star_scores: Vec::new(),
+    }
+}
+
+fn app(level: Level) -> App {
+    let editor = LevelEditor::new(level);
+
+    app_with(|app| {
+        app.insert_resource(editor.test_session(1.));
+    })
+}
+
+fn state(app: &App) -> GameState {
+    *app.world().resource::<State<GameState>>().get()
+}
+
+/// Обмен (0, 2)-(0, 3) собирает три круга в нижнем ряду.
+fn play_match(app: &mut App) {
+    let mut forms = pattern_board(app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+
+    load_board(app, forms);
+    swap(app, (0, 2), (0, 3));
+    settle(app);
+}
+
+#[test]
+fn paint_undo_and_redo() {
+    let mut editor = LevelEditor::new(level("...\n...\n..."));
+    editor.set_brush(LayoutCell::Blocker);
+    editor.paint((0, 0).into());
+    editor.set_brush(LayoutCell::Special(Form::Rhombus));
+    editor.paint((2, 1).into());
+
+    assert_eq!(editor.level().layout.to_string(), ". r .\n. . .\n@ . .\n");
+
+    editor.undo();
+    assert_eq!(editor.level().layout.get((2, 1)), LayoutCell::Empty);
+    editor.redo();
+    assert_eq!(
+        editor.level().layout.get((2, 1)),
+        LayoutCell::Special(Form::Rhombus)
+    );
+
+    // Новая правка после отмены стирает то, что можно было вернуть.
+    editor.undo();
+    editor.set_brush(LayoutCell::Hole);
+    editor.paint((1, 1).into());
+    assert!(!editor.can_redo());
+
+    editor.undo();
+    editor.undo();
+    assert_eq!(editor.level().layout, BoardLayout::empty(3, 3));
+    assert!(!editor.can_undo());
+}
+
+#[test]
+fn stroke_is_undone_at_once() {
+    let mut editor = LevelEditor::new(level("...\n...\n..."));
+    editor.begin_stroke();
+    for col_id in 0..3 {
+        editor.paint((0, col_id).into());
+    }
+    editor.end_stroke();
+    editor.paint((1, 1).into());
+
+    editor.undo();
+    assert_eq!(editor.level().layout.to_string(), ". . .\n. . .\nC C C\n");
+    editor.undo();
+    assert_eq!(editor.level().layout, BoardLayout::empty(3, 3));
+    assert!(!editor.can_undo());
+}
+
+#[test]
+fn level_file_follows_name() {
+    let mut editor = LevelEditor::new(level("..."));
+    editor.set_name("  My first level!  ");
+    editor.set_name(" ");
+
+    assert_eq!(editor.level().name, "My first level!");
+    let path = editor.path().unwrap();
+    assert_eq!(path.file_name().unwrap(), "my-first-level.level.ron");
+    assert_eq!(path.parent().unwrap().file_name().unwrap(), "levels");
+
+    editor.undo();
+    assert_eq!(editor.level().name, "Test");
+    assert_eq!(
+        editor.path().unwrap().file_name().unwrap(),
+        "test.level.ron"
+    );
+}
+
+#[test]
+fn resize_keeps_bottom_left_corner() {
+    let mut editor = LevelEditor::new(level("...\n...\nC.."));
+
+    editor.resize(5, 4);
+    assert_eq!(
+        editor.level().layout.to_string(),
+        ". . . . .\n. . . . .\n. . . . .\nC . . . .\n"
+    );
+
+    // Меньше трёх клеток и больше видимой части игровой доски сторона не бывает.
+    editor.resize(1, 100);
+    assert_eq!(editor.level().layout.width(), 3);
+    assert_eq!(editor.level().layout.height(), 10);
+    assert_eq!(
+        editor.level().layout.get((0, 0)),
+        LayoutCell::Tile(Form::Circle)
+    );
+
+    editor.undo();
+    editor.undo();
+    assert_eq!(editor.level().layout.to_string(), ". . .\n. . .\nC . .\n");
+}
+
+#[test]
+fn goals_and_moves_are_set_and_cleared() {
+    let mut editor = LevelEditor::new(level("..."));
+
+    editor.set_moves(Some(20));
+    editor.set_score_goal(500);
+    editor.set_collect_goal(Form::Square, 10);
+    editor.set_score_goal(300);
+    assert_eq!(editor.level().moves, Some(20));
+    assert_eq!(
+        editor.level().goals,
+        [
+            LevelGoal::Score(300),
+            LevelGoal::Collect {
+                form: Form::Square,
+                count: 10
+            }
+        ]
+    );
+
+    editor.set_moves(Some(0));
+    editor.set_score_goal(0);
+    assert_eq!(editor.level().moves, None);
+    assert_eq!(editor.collect_goal(Form::Square), 10);
+    assert_eq!(editor.score_goal(), None);
+}
+
+#[test]
+fn level_file_round_trips() {
+    let mut level = level("C # @\nt . S");
+    level.moves = Some(15);
+    level.goals = vec![
+        LevelGoal::Score(1000),
+        LevelGoal::Collect {
+            form: Form::Triangle,
+            count: 12,
+        },
+    ];
+    level.star_scores = vec![1500, 2500];
+    let path = std::env::temp_dir().join(format!("tile-matching-{}.level.ron", std::process::id()));
+
+    level.save(&path).unwrap();
+    let loaded = Level::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.unwrap(), level);
+}
+
+#[test]
+fn test_play_starts_from_layout() {
+    let layout = (0..4)
+        .rev()
+        .map(|row_id| {
+            (0..3)
+                .map(|col_id| pattern(row_id, col_id).symbol())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let app = app(level(&layout));
+    let board = board(&app);
+
+    for row_id in 0..4 {
+        for col_id in 0..3 {
+            assert_eq!(
+                board[row_id][col_id].tile.unwrap().form,
+                pattern(row_id, col_id)
+            );
+        }
+    }
+}
+
+#[test]
+fn holes_and_blockers_stay_put_and_specials_play() {
+    let mut app = app(level("@ . . .\n# . . .\n. . . t"));
+    settle(&mut app);
+    let spawned = board(&app);
+
+    assert_eq!(spawned[0][3].tile.unwrap().form, Form::Triangle);
+    assert_eq!(spawned[1][0].kind, CellKind::Hole);
+    assert_eq!(spawned[2][0].kind, CellKind::Blocker);
+
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(&mut app, forms);
+    let above_blocker = board(&app)[3][0].tile.unwrap().entity;
+    let score = app.world().resource::<ScoreStorage>().total();
+
+    swap(&mut app, (0, 2), (0, 3));
+    settle(&mut app);
+    let board = board(&app);
+
+    // Фишка над преградой падает сквозь преграду и дыру, а сами они остаются пустыми на месте.
+    assert_eq!(board[0][0].tile.unwrap().entity, above_blocker);
+    assert!(board[1][0].tile.is_none());
+    assert!(board[2][0].tile.is_none());
+    assert_eq!(board[2][0].kind, CellKind::Blocker);
+    assert_eq!(
+        app.world().resource::<ScoreStorage>().total(),
+        score + 3 * SCORE_PER_TILE
+    );
+}
+
+#[test]
+fn level_move_limit_ends_game() {
+    let mut level = level("...");
+    level.moves = Some(1);
+    let mut app = app(level);
+    settle(&mut app);
+
+    play_match(&mut app);
+
+    assert_eq!(state(&app), GameState::GameOver);
+}
+
+#[test]
+fn meeting_goals_ends_level() {
+    let mut level = level("...");
+    level.goals = vec![LevelGoal::Score(10_000)];
+    let mut app = app(level);
+    settle(&mut app);
+    app.insert_resource(ScoreStorage::solo(10_000 - SCORE_PER_TILE));
+    assert_eq!(state(&app), GameState::Playing);
+
+    play_match(&mut app);
+
+    assert_eq!(state(&app), GameState::GameOver);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use bevy::prelude::*;
+use tile_matching::{
+    board::Form,
+    effects::{EffectsPlugin, Shard},
+    settings::Settings,
+};
+
+use common::{app_with, load_board, pattern_board, settle, swap};
+
+fn app(enabled: bool) -> App {
+    app_with(|app| {
+        app.add_plugins(EffectsPlugin).insert_resource(Settings {
+            effects: enabled,
+            ..Default::default()
+        });
+    })
+}
+
+/// Обменивает фишки так, что снимается ровно три круга, и ждёт, пока доска успокоится.
+/// Возвращает, сколько осколков было на экране в самый насыщенный момент.
+fn match_three(app: &mut App) -> usize {
+    let mut forms = pattern_board(app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(app, forms);
+
+    swap(app, (0, 2), (0, 3));
+
+    let mut max_shards = 0;
+    for _ in 0..120 {
+        app.update();
+        max_shards = max_shards.max(shards(app));
+    }
+    settle(app);
+
+    max_shards
+}
+
+fn shards(app: &mut App) -> usize {
+    let world = app.world_mut();
+
+    world.query::<&Shard>().iter(world).count()
+}
+
+fn camera(app: &mut App) -> Vec3 {
+    let world = app.world_mut();
+
+    world
+        .query_filtered::<&Transform, With<Camera2d>>()
+        .single(world)
+        .unwrap()
+        .translation
+}
+
+#[test]
+fn cleared_tiles_burst_into_shards() {
+    let mut app = app(true);
+
+    assert!(match_three(&mut app) >= 3);
+    assert_eq!(shards(&mut app), 0, "Shards must fade out");
+}
+
+#[test]
+fn shake_returns_camera_to_its_place() {
+    let base = Vec3::new(40., -25., 0.);
+    let mut app = app(true);
+    app.world_mut()
+        .spawn((Camera2d, Transform::from_translation(base)));
+
+    // Четыре круга в ряд трясут камеру.
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    forms[0][4] = Form::Square;
+    forms[1][2] = Form::Circle;
+    load_board(&mut app, forms);
+    swap(&mut app, (0, 2), (1, 2));
+
+    let mut max_offset: f32 = 0.;
+    for _ in 0..120 {
+        app.update();
+        max_offset = max_offset.max(camera(&mut app).distance(base));
+    }
+    settle(&mut app);
+
+    assert!(max_offset > 0.);
+    assert!(max_offset <= 12.);
+    assert_eq!(camera(&mut app), base);
+}
+
+#[test]
+fn disabled_effects_spawn_nothing() {
+    let mut app = app(false);
+
+    assert_eq!(match_three(&mut app), 0);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use bevy::prelude::*;
+use tile_matching::{
+    GameMode, GameSession, ScoreStorage,
+    board::{Board, BoardIndex, Form},
+    gamepad::{GamepadPlugin, PadCursor},
+    rules::SCORE_PER_TILE,
+    versus::Player,
+};
+
+use common::{app_with, load_board_of, pattern_board, settle};
+
+fn app() -> (App, [Entity; 2]) {
+    let mut app = app_with(|app| {
+        app.add_plugins(GamepadPlugin)
+            .insert_resource(GameSession::new(GameMode::Race, 1.));
+    });
+    let pads = [(); 2].map(|_| app.world_mut().spawn(Gamepad::default()).id());
+
+    (app, pads)
+}
+
+/// Нажимает и отпускает кнопку за один `update`. Без `InputPlugin` нажатие само не сбрасывается.
+fn press(app: &mut App, pad: Entity, button: GamepadButton) {
+    let world = app.world_mut();
+    world
+        .get_mut::<Gamepad>(pad)
+        .unwrap()
+        .digital_mut()
+        .press(button);
+    app.update();
+
+    let world = app.world_mut();
+    let mut gamepad = world.get_mut::<Gamepad>(pad).unwrap();
+    gamepad.digital_mut().release(button);
+    gamepad.digital_mut().clear();
+}
+
+fn cursor(app: &mut App, player: Player) -> Option<BoardIndex> {
+    let world = app.world_mut();
+
+    world
+        .query_filtered::<(&Player, Option<&PadCursor>), With<Board>>()
+        .iter(world)
+        .find(|(owner, _)| **owner == player)
+        .and_then(|(_, cursor)| cursor.map(|cursor| cursor.0))
+}
+
+fn scores(app: &App) -> [usize; 2] {
+    let score = app.world().resource::<ScoreStorage>();
+
+    Player::ALL.map(|player| score.of(player))
+}
+
+#[test]
+fn each_gamepad_plays_its_own_board() {
+    let (mut app, pads) = app();
+    // Одинаковые случайные доски могли сами сыграть каскадом.
+    settle(&mut app);
+    let before = scores(&app);
+
+    press(&mut app, pads[1], GamepadButton::DPadRight);
+    assert_eq!(cursor(&mut app, Player::Two), Some((0, 0).into()));
+    assert_eq!(cursor(&mut app, Player::One), None);
+
+    // Нижний ряд второго игрока: C C A C ..., обмен (0, 2)-(0, 3) собирает три круга.
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board_of(&mut app, Player::Two, forms);
+
+    press(&mut app, pads[1], GamepadButton::DPadRight);
+    press(&mut app, pads[1], GamepadButton::DPadRight);
+    press(&mut app, pads[1], GamepadButton::South);
+    press(&mut app, pads[1], GamepadButton::DPadRight);
+    press(&mut app, pads[1], GamepadButton::South);
+    assert_eq!(cursor(&mut app, Player::Two), Some((0, 3).into()));
+    settle(&mut app);
+
+    assert_eq!(scores(&app), [before[0], before[1] + 3 * SCORE_PER_TILE]);
+
+    press(&mut app, pads[0], GamepadButton::DPadUp);
+    assert_eq!(cursor(&mut app, Player::One), Some((0, 0).into()));
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use tile_matching::{
+    ScoreStorage,
+    board::{BoardLayout, Form},
+    messages::{BoardSettled, MatchFound, ScoreChanged, SwapRequested, TilesCleared, TilesFell},
+    rules::{Grid, MatchShape},
+};
+
+use common::{
+    app, app_with, assert_settled, board, load_board, pattern, pattern_board, score, settle, swap,
+    visible_forms,
+};
+
+/// Чего ждать от обмена по правилам из `rules`. Каскады в тестах не доходят до скрытых рядов,
+/// поэтому видимая часть не зависит от случайных новых фишек.
+fn expected_after_swap(
+    app: &App,
+    from: (usize, usize),
+    to: (usize, usize),
+) -> (Vec<Vec<Form>>, usize) {
+    let mut grid = Grid::from(board(app));
+    let resolution = grid
+        .try_swap(from.into(), to.into(), &mut ChaCha8Rng::seed_from_u64(0))
+        .expect("Swap must be valid");
+
+    let forms = (0..grid.visible_height())
+        .map(|row_id| {
+            (0..grid.width())
+                .map(|col_id| grid.get((row_id, col_id).into()).unwrap())
+                .collect()
+        })
+        .collect();
+
+    (forms, resolution.score)
+}
+
+/// Собирает сообщения `M`, чтобы проверить их после того, как доска успокоится.
+#[derive(Resource)]
+struct Received<M: Message>(Vec<M>);
+
+fn listen<M: Message + Clone>(app: &mut App) {
+    app.insert_resource(Received::<M>(Vec::new())).add_systems(
+        Update,
+        |mut reader: MessageReader<M>, mut received: ResMut<Received<M>>| {
+            received.0.extend(reader.read().cloned());
+        },
+    );
+}
+
+fn received<M: Message>(app: &App) -> &[M] {
+    &app.world().resource::<Received<M>>().0
+}
+
+#[test]
+fn matching_swap_clears_run_and_drops_tiles() {
+    let mut app = app();
+    let mut forms = pattern_board(&app);
+    // Нижний ряд: C C A C R ..., обмен A и C даёт три круга подряд.
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(&mut app, forms);
+
+    let (expected, expected_score) = expected_after_swap(&app, (0, 2), (0, 3));
+
+    swap(&mut app, (0, 2), (0, 3));
+    settle(&mut app);
+
+    assert_settled(&mut app);
+    assert_eq!(score(&app), 30);
+    assert_eq!(score(&app), expected_score);
+    assert_eq!(visible_forms(&app), expected);
+    assert_eq!(
+        visible_forms(&app)[0][..4],
+        [pattern(1, 0), pattern(1, 1), pattern(1, 2), Form::Annulus]
+    );
+}
+
+#[test]
+fn matching_swap_emits_messages() {
+    let mut app = app();
+    let mut forms = pattern_board(&app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(&mut app, forms);
+    settle(&mut app);
+
+    listen::<SwapRequested>(&mut app);
+    listen::<MatchFound>(&mut app);
+    listen::<TilesCleared>(&mut app);
+    listen::<TilesFell>(&mut app);
+    listen::<BoardSettled>(&mut app);
+    listen::<ScoreChanged>(&mut app);
+
+    swap(&mut app, (0, 2), (0, 3));
+    settle(&mut app);
+
+    let requested = received::<SwapRequested>(&app);
+    assert_eq!(requested.len(), 1);
+    assert_eq!(
+        (requested[0].from, requested[0].to),
+        ((0, 2).into(), (0, 3).into())
+    );
+
+    let found = received::<MatchFound>(&app);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].form, Form::Circle);
+    assert_eq!(found[0].shape, MatchShape::Line);
+    assert_eq!(found[0].cells.len(), 3);
+
+    let cleared = received::<TilesCleared>(&app);
+    assert_eq!(cleared.len(), 1);
+    assert_eq!(cleared[0].cells.len(), 3);
+
+    // Над каждой снятой фишкой падает вся колонка, включая скрытые ряды.
+    let fell = received::<TilesFell>(&app);
+    assert_eq!(fell.len(), 1);
+    assert_eq!(fell[0].falls.len(), 3 * (board(&app).height() - 1));
+
+    assert_eq!(received::<BoardSettled>(&app).len(), 1);
+    let score_changed = received::<ScoreChanged>(&app);
+    assert_eq!(score_changed.len(), 1);
+    assert_eq!((score_changed[0].previous, score_changed[0].score), (0, 30));
+}
+
+#[test]
+fn continued_game_does_not_report_restored_score() {
+    let mut app = app_with(|app| {
+        app.insert_resource(ScoreStorage::solo(500));
+        listen::<ScoreChanged>(app);
+    });
+    settle(&mut app);
+
+    // Счёт продолженной партии восстановлен, а не набран с нуля.
+    assert!(
+        received::<ScoreChanged>(&app)
+            .iter()
+            .all(|changed| changed.previous >= 500)
+    );
+}
+
+#[test]
+fn swap_without_match_is_reverted() {
+    let mut app = app();
+    let forms = pattern_board(&app);
+    load_board(&mut app, forms);
+    let before = visible_forms(&app);
+
+    swap(&mut app, (5, 5), (5, 6));
+    settle(&mut app);
+
+    assert_settled(&mut app);
+    assert_eq!(score(&app), 0);
+    assert_eq!(visible_forms(&app), before);
+}
+
+#[test]
+fn falling_tiles_cascade() {
+    let mut app = app();
+    // Обмен R и C в нижнем ряду даёт три круга, после их снятия ромбы падают и собираются в ряд.
+    let layout: BoardLayout = "
+        A S R C T A S R C T
+        R C T A S R C T A S
+        T A S R C T A S R C
+        S R C T A S R C T A
+        C T A S R C T A S R
+        A S R C T A S R C T
+        R C T A S R C T A S
+        T A S R C T A S R C
+        S R C T A S R C T A
+        C T A S R C T A S R
+        - - - - - - - - - -
+        A S R C T A S R C T
+        R C T A S R C T A S
+        T A S R C T A S R C
+        S R C T A S R C T A
+        C T A S R C T A S R
+        A S R C T A S R C T
+        R C T A S R C T A S
+        T A S R C T A S R C
+        S R R T A S R C T A
+        C C R C R C T A S R
+    "
+    .parse()
+    .unwrap();
+    load_board(&mut app, layout.forms().unwrap());
+
+    let (expected, expected_score) = expected_after_swap(&app, (0, 2), (0, 3));
+
+    swap(&mut app, (0, 2), (0, 3));
+    settle(&mut app);
+
+    assert_settled(&mut app);
+    assert!(expected_score > 30, "Layout must cascade");
+    assert_eq!(score(&app), expected_score);
+    assert_eq!(visible_forms(&app), expected);
+}
+
+#[test]
+fn random_board_settles() {
+    let mut app = app();
+
+    settle(&mut app);
+
+    assert_settled(&mut app);
+}
@@ -0,0 +1,66 @@
+use std::{collections::BTreeSet, fs};
+
+use tile_matching::locale::{Language, Locale};
+
+fn message_ids(file: &str) -> BTreeSet<String> {
+    let path = format!("{}/assets/locales/{file}", env!("CARGO_MANIFEST_DIR"));
+
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.split_once(" ="))
+        .map(|(id, _)| id.to_string())
+        .filter(|id| !id.starts_with([' ', '#']))
+        .collect()
+}
+
+#[test]
+fn russian_translates_every_message() {
+    let english = message_ids("en.ftl");
+    let russian = message_ids("ru.ftl");
+
+    assert!(english.contains("game-title"));
+    assert_eq!(
+        english.difference(&russian).collect::<Vec<_>>(),
+        Vec::<&String>::new()
+    );
+}
+
+#[test]
+fn unknown_message_falls_back_to_its_id() {
+    let locale = Locale::new(Language::Russian);
+
+    assert_eq!(locale.text("no-such-message"), "no-such-message");
+    assert_eq!(locale.text("menu-back"), "Назад");
+}
+
+#[test]
+fn plurals_follow_language_rules() {
+    let russian = Locale::new(Language::Russian);
+    let english = Locale::new(Language::English);
+    let circles =
+        |locale: &Locale, count: u32| locale.format("form-circle", &[("count", count.into())]);
+
+    assert_eq!(
+        [1, 3, 5, 21].map(|count| circles(&russian, count)),
+        ["круг", "круга", "кругов", "круг"]
+    );
+    assert_eq!(
+        [1, 3, 21].map(|count| circles(&english, count)),
+        ["circle", "circles", "circles"]
+    );
+}
+
+#[test]
+fn numbers_are_grouped_per_language() {
+    let score =
+        |language, score: u32| Locale::new(language).format("score", &[("score", score.into())]);
+
+    assert_eq!(score(Language::English, 999), "Score: 999");
+    assert_eq!(score(Language::English, 12345), "Score: 12,345");
+    assert_eq!(score(Language::Russian, 1234), "Счёт: 1234");
+    assert_eq!(
+        score(Language::Russian, 1234567),
+        "Счёт: 1\u{a0}234\u{a0}567"
+    );
+}
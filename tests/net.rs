@@ -0,0 +1,160 @@
+mod common;
+
+use std::net::{TcpListener, TcpStream};
+
+use bevy::prelude::*;
+use tile_matching::{
+    GameMode, GameSession, GameState, ScoreStorage,
+    board::Form,
+    net::{NetMessage, NetPeer, NetPlugin, NetStatus, board_hash},
+    versus::Player,
+};
+
+use common::{app_with, board, load_board, pattern_board, swap};
+
+/// Столько кадров хватает, чтобы ход дошёл до соперника и обе доски успокоились.
+const SETTLE_FRAMES: usize = 600;
+
+/// Хозяин и второй игрок в одном процессе, соединённые через localhost.
+fn connected_apps(seeds: [u64; 2]) -> [App; 2] {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let joined = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (hosted, _) = listener.accept().unwrap();
+
+    [
+        (hosted, Player::One, seeds[0]),
+        (joined, Player::Two, seeds[1]),
+    ]
+    .map(|(stream, local, seed)| {
+        app_with(|app| {
+            app.add_plugins(NetPlugin)
+                .insert_resource(GameSession::new(GameMode::Versus, 1.).with_seed(seed))
+                .insert_resource(NetPeer::new(stream, local).unwrap());
+        })
+    })
+}
+
+fn settle_both(apps: &mut [App; 2]) {
+    for _ in 0..SETTLE_FRAMES {
+        for app in apps.iter_mut() {
+            app.update();
+        }
+    }
+}
+
+fn hash(app: &App) -> u64 {
+    board_hash(board(app), app.world().resource::<ScoreStorage>())
+}
+
+fn status(app: &App) -> NetStatus {
+    app.world().resource::<NetPeer>().status()
+}
+
+fn player(app: &App) -> Player {
+    app.world()
+        .resource::<GameSession>()
+        .versus()
+        .unwrap()
+        .player()
+}
+
+/// Одинаковая доска у обоих, на которой обмен (0, 2)-(0, 3) собирает три круга.
+fn load_match(apps: &mut [App; 2]) {
+    let mut forms = pattern_board(&apps[0]);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+
+    for app in apps.iter_mut() {
+        load_board(app, forms.clone());
+    }
+}
+
+#[test]
+fn messages_survive_encoding_in_pieces() {
+    let messages = [
+        NetMessage::Hello {
+            seed: 42,
+            animation_speed: 1.5,
+        },
+        NetMessage::Swap {
+            turn: 7,
+            from: (0, 2).into(),
+            to: (0, 3).into(),
+        },
+        NetMessage::Hash {
+            turn: 7,
+            hash: u64::MAX,
+        },
+    ];
+
+    let mut bytes = Vec::new();
+    for message in &messages {
+        message.encode(&mut bytes);
+    }
+
+    let mut decoded = Vec::new();
+    let mut rest = &bytes[..];
+    while !rest.is_empty() {
+        assert_eq!(
+            NetMessage::decode(&rest[..rest.len().min(3)]).unwrap(),
+            None,
+            "Partial message must wait for the rest"
+        );
+
+        let (message, len) = NetMessage::decode(rest).unwrap().unwrap();
+        decoded.push(message);
+        rest = &rest[len..];
+    }
+    assert_eq!(decoded, messages);
+}
+
+#[test]
+fn foreign_and_broken_messages_are_rejected() {
+    assert!(NetMessage::decode(&[9]).is_err());
+
+    let mut swap = Vec::new();
+    NetMessage::Swap {
+        turn: 0,
+        from: (0, 0).into(),
+        to: (0, 1).into(),
+    }
+    .encode(&mut swap);
+    swap[6] = 200;
+    assert!(NetMessage::decode(&swap).is_err());
+}
+
+#[test]
+fn swaps_travel_both_ways_in_lockstep() {
+    let mut apps = connected_apps([7, 7]);
+    settle_both(&mut apps);
+
+    load_match(&mut apps);
+    swap(&mut apps[0], (0, 2), (0, 3));
+    settle_both(&mut apps);
+
+    assert_eq!(hash(&apps[0]), hash(&apps[1]));
+    assert!(apps.iter().all(|app| player(app) == Player::Two));
+
+    load_match(&mut apps);
+    swap(&mut apps[1], (0, 2), (0, 3));
+    settle_both(&mut apps);
+
+    assert_eq!(hash(&apps[0]), hash(&apps[1]));
+    assert!(apps.iter().all(|app| player(app) == Player::One));
+    assert!(apps.iter().all(|app| status(app) == NetStatus::Connected));
+}
+
+#[test]
+fn different_seeds_are_detected_as_desync() {
+    let mut apps = connected_apps([1, 2]);
+    settle_both(&mut apps);
+
+    for app in &apps {
+        assert_eq!(status(app), NetStatus::Desync { turn: 0 });
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::GameOver
+        );
+    }
+}
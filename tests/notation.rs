@@ -0,0 +1,116 @@
+use bevy::prelude::Entity;
+use tile_matching::board::{
+    Board, BoardLayout, Cell, CellKind, Form, LayoutCell, ParseLayoutError, Tile,
+};
+
+#[test]
+fn parses_rows_top_down() {
+    let layout: BoardLayout = "
+        C S #
+        T . A
+    "
+    .parse()
+    .unwrap();
+
+    assert_eq!(layout.width(), 3);
+    assert_eq!(layout.height(), 2);
+    assert_eq!(layout.get((0, 0)), LayoutCell::Tile(Form::Triangle));
+    assert_eq!(layout.get((0, 1)), LayoutCell::Empty);
+    assert_eq!(layout.get((1, 1)), LayoutCell::Tile(Form::Square));
+    assert_eq!(layout.get((1, 2)), LayoutCell::Hole);
+}
+
+#[test]
+fn spaces_and_separators_are_optional() {
+    let spaced: BoardLayout = "C S T\n- - -\nR A C".parse().unwrap();
+    let compact: BoardLayout = "CST\n\nRAC".parse().unwrap();
+
+    assert_eq!(spaced, compact);
+}
+
+#[test]
+fn display_round_trips() {
+    let text = "C S T R A\n. # C S T\n";
+    let layout: BoardLayout = text.parse().unwrap();
+
+    assert_eq!(layout.to_string(), text);
+    assert_eq!(layout.to_string().parse::<BoardLayout>().unwrap(), layout);
+}
+
+#[test]
+fn blockers_and_specials_round_trip() {
+    let layout: BoardLayout = "@ c\nS a".parse().unwrap();
+
+    assert_eq!(layout.get((1, 0)), LayoutCell::Blocker);
+    assert_eq!(layout.get((1, 1)), LayoutCell::Special(Form::Circle));
+    assert_eq!(layout.get((0, 1)), LayoutCell::Special(Form::Annulus));
+    assert_eq!(layout.form_at((0, 1)), Some(Form::Annulus));
+    assert_eq!(layout.form_at((1, 0)), None);
+    assert_eq!(layout.to_string(), "@ c\nS a\n");
+}
+
+#[test]
+fn forms_require_full_board() {
+    let full: BoardLayout = "C S\nT R".parse().unwrap();
+    let with_hole: BoardLayout = "C #\nT R".parse().unwrap();
+
+    assert_eq!(
+        full.forms(),
+        Some(vec![
+            vec![Form::Triangle, Form::Rhombus],
+            vec![Form::Circle, Form::Square],
+        ])
+    );
+    assert_eq!(with_hole.forms(), None);
+}
+
+#[test]
+fn reports_bad_input() {
+    assert_eq!("".parse::<BoardLayout>(), Err(ParseLayoutError::NoRows));
+    assert_eq!(
+        "C S\nC X".parse::<BoardLayout>(),
+        Err(ParseLayoutError::UnknownSymbol {
+            line: 2,
+            symbol: 'X'
+        })
+    );
+    assert_eq!(
+        "C S\nC S T".parse::<BoardLayout>(),
+        Err(ParseLayoutError::RowLength {
+            line: 2,
+            expected: 2,
+            found: 3
+        })
+    );
+}
+
+#[test]
+fn board_dump_marks_hidden_rows() {
+    let mut board = Board::new();
+    for row_id in 0..board.height() {
+        let row = (0..board.width())
+            .map(|col_id| Cell {
+                // Верхний ряд оставляем пустым, как будто новые фишки ещё не появились.
+                tile: (row_id + 1 < board.height()).then_some(Tile {
+                    form: Form::ALL[(row_id + col_id) % Form::ALL.len()],
+                    entity: Entity::PLACEHOLDER,
+                    select_area_entity: Entity::PLACEHOLDER,
+                    visual_entity: Entity::PLACEHOLDER,
+                }),
+                kind: CellKind::Open,
+            })
+            .collect();
+        board.push_row(row);
+    }
+
+    let dump = board.to_string();
+    let lines = dump.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), board.height() + 1);
+    assert_eq!(lines[0], ". . . . . . . . . .");
+    assert_eq!(lines[board.visible_height()], "- - - - - - - - - -");
+    assert_eq!(lines.last(), Some(&"C S T R A C S T R A"));
+
+    let layout: BoardLayout = dump.parse().unwrap();
+    assert_eq!(layout, BoardLayout::from(&board));
+}
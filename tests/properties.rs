@@ -0,0 +1,207 @@
+mod common;
+
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use tile_matching::{
+    board::{Board, BoardIndex, Form},
+    rules::Grid,
+};
+
+use common::{app, assert_settled, board, load_board, score, settle, swap, visible_forms};
+
+const MAX_SIZE: usize = 10;
+
+/// Доска произвольного размера, клетки которой заполнены с вероятностью `fill`.
+fn grid(fill: f64) -> impl Strategy<Value = Grid> {
+    (3..=MAX_SIZE, 3..=MAX_SIZE, 3..=Form::ALL.len()).prop_flat_map(
+        move |(width, visible_height, form_count)| {
+            let cells = visible_height * 2 * width;
+
+            let cell = if fill < 1. {
+                prop::option::weighted(fill, 0..form_count).boxed()
+            } else {
+                (0..form_count).prop_map(Some).boxed()
+            };
+
+            prop::collection::vec(cell, cells).prop_map(move |forms| {
+                let mut grid = Grid::new(width, visible_height, form_count);
+                for (cell_id, form) in forms.into_iter().enumerate() {
+                    let idx = (cell_id / width, cell_id % width).into();
+                    grid.set(idx, form.map(|form| Form::ALL[form]));
+                }
+                grid
+            })
+        },
+    )
+}
+
+fn full_grid() -> impl Strategy<Value = Grid> {
+    grid(1.)
+}
+
+/// Соседняя пара видимых клеток на доске размера `width` x `visible_height`.
+fn neighbours(
+    width: usize,
+    visible_height: usize,
+) -> impl Strategy<Value = (BoardIndex, BoardIndex)> {
+    (0..visible_height, 0..width, any::<bool>()).prop_filter_map(
+        "Neighbour is off board",
+        move |(row_id, col_id, up)| {
+            let neighbour = if up {
+                (row_id + 1, col_id)
+            } else {
+                (row_id, col_id + 1)
+            };
+
+            (neighbour.0 < visible_height && neighbour.1 < width)
+                .then(|| ((row_id, col_id).into(), neighbour.into()))
+        },
+    )
+}
+
+fn grid_with_swap() -> impl Strategy<Value = (Grid, (BoardIndex, BoardIndex), u64)> {
+    full_grid().prop_flat_map(|grid| {
+        let swaps = neighbours(grid.width(), grid.visible_height());
+        (Just(grid), swaps, any::<u64>())
+    })
+}
+
+fn cells(grid: &Grid) -> impl Iterator<Item = BoardIndex> + '_ {
+    (0..grid.height())
+        .flat_map(|row_id| (0..grid.width()).map(move |col_id| (row_id, col_id).into()))
+}
+
+fn form_counts(grid: &Grid) -> [usize; 5] {
+    let mut counts = [0; 5];
+    for form in cells(grid).filter_map(|idx| grid.get(idx)) {
+        counts[Form::ALL.iter().position(|other| *other == form).unwrap()] += 1;
+    }
+    counts
+}
+
+fn assert_no_gaps(grid: &Grid) {
+    for col_id in 0..grid.width() {
+        let column = (0..grid.height())
+            .map(|row_id| grid.get((row_id, col_id).into()))
+            .collect::<Vec<_>>();
+        let first_empty = column
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(column.len());
+
+        assert!(
+            column[first_empty..].iter().all(Option::is_none),
+            "Column {col_id} has a tile above an empty cell: {column:?}"
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn resolve_leaves_no_runs(grid in full_grid(), seed in any::<u64>()) {
+        let mut grid = grid;
+        grid.resolve(&mut ChaCha8Rng::seed_from_u64(seed));
+
+        prop_assert!(grid.find_runs().is_empty());
+        prop_assert!(cells(&grid).all(|idx| grid.get(idx).is_some()));
+    }
+
+    #[test]
+    fn runs_through_agrees_with_find_runs((grid, (a, b), _) in grid_with_swap()) {
+        let expected: Vec<_> = grid
+            .find_runs()
+            .into_iter()
+            .filter(|run| run.cells.contains(&a) || run.cells.contains(&b))
+            .collect();
+        let mut found = grid.runs_through(&[a, b]);
+
+        found.retain(|run| !expected.contains(run));
+        prop_assert!(found.is_empty(), "Unexpected runs: {:?}", found);
+        prop_assert_eq!(grid.runs_through(&[a, b]).len(), expected.len());
+    }
+
+    #[test]
+    fn collapse_leaves_no_gaps(grid in grid(0.6)) {
+        let mut collapsed = grid.clone();
+        collapsed.collapse();
+
+        assert_no_gaps(&collapsed);
+        prop_assert_eq!(form_counts(&collapsed), form_counts(&grid));
+    }
+
+    #[test]
+    fn swap_conserves_tiles((grid, (a, b), _) in grid_with_swap()) {
+        let mut swapped = grid.clone();
+        swapped.swap(a, b);
+
+        prop_assert_eq!(form_counts(&swapped), form_counts(&grid));
+        prop_assert_eq!(swapped.get(a), grid.get(b));
+        prop_assert_eq!(swapped.get(b), grid.get(a));
+    }
+
+    #[test]
+    fn unmatched_swap_is_reverted((grid, (a, b), seed) in grid_with_swap()) {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut grid = grid;
+        grid.resolve(&mut rng);
+
+        let before = grid.clone();
+        let resolution = grid.try_swap(a, b, &mut rng);
+
+        if before.runs_after_swap(a, b).is_empty() {
+            prop_assert!(resolution.is_none());
+            prop_assert_eq!(grid, before);
+        } else {
+            prop_assert!(resolution.is_some_and(|resolution| resolution.score > 0));
+        }
+    }
+}
+
+fn game_board() -> impl Strategy<Value = Vec<Vec<Form>>> {
+    let board = Board::new();
+
+    prop::collection::vec(
+        prop::collection::vec(prop::sample::select(Form::ALL.to_vec()), board.width()),
+        board.height(),
+    )
+}
+
+fn game_swap() -> impl Strategy<Value = (BoardIndex, BoardIndex)> {
+    let board = Board::new();
+
+    neighbours(board.width(), board.visible_height())
+}
+
+proptest! {
+    // Каждый случай гоняет игру на сотни тиков, поэтому случаев меньше.
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    /// Те же инварианты, но для систем игры, а не для `rules`.
+    #[test]
+    fn game_swap_keeps_invariants(forms in game_board(), (a, b) in game_swap()) {
+        let mut app = app();
+        load_board(&mut app, forms);
+        settle(&mut app);
+        assert_settled(&mut app);
+
+        let before = Grid::from(board(&app));
+        prop_assert!(before.find_runs().is_empty(), "Runs left after cascades:\n{}", board(&app));
+        let visible_before = visible_forms(&app);
+        let score_before = score(&app);
+
+        swap(&mut app, (a.row_id(), a.col_id()), (b.row_id(), b.col_id()));
+        settle(&mut app);
+        assert_settled(&mut app);
+
+        let after = Grid::from(board(&app));
+        prop_assert!(after.find_runs().is_empty(), "Runs left after swap:\n{}", board(&app));
+
+        if before.runs_after_swap(a, b).is_empty() {
+            prop_assert_eq!(visible_forms(&app), visible_before);
+            prop_assert_eq!(score(&app), score_before);
+        } else {
+            prop_assert!(score(&app) > score_before);
+        }
+    }
+}
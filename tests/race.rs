@@ -0,0 +1,148 @@
+mod common;
+
+use bevy::prelude::*;
+use tile_matching::{
+    GameMode, GameSession, GameState, ScoreStorage,
+    autoplay::{Autoplay, AutoplayPlugin},
+    board::Form,
+    race::RACE_TARGET_SCORE,
+    rules::SCORE_PER_TILE,
+    versus::Player,
+};
+
+use common::{app_with, board_of, load_board_of, pattern_board, settle, swap_on};
+
+fn app() -> App {
+    app_with(|app| {
+        app.insert_resource(GameSession::new(GameMode::Race, 1.));
+    })
+}
+
+fn forms(app: &App, player: Player) -> Vec<Vec<Option<Form>>> {
+    board_of(app, player)
+        .into_iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.tile.map(|tile| tile.form))
+                .collect()
+        })
+        .collect()
+}
+
+fn scores(app: &App) -> [usize; 2] {
+    let score = app.world().resource::<ScoreStorage>();
+
+    Player::ALL.map(|player| score.of(player))
+}
+
+/// Собирает на доске игрока три круга в нижнем ряду.
+fn play_match(app: &mut App, player: Player) {
+    let mut forms = pattern_board(app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+
+    load_board_of(app, player, forms);
+    swap_on(app, player, (0, 2), (0, 3));
+    settle(app);
+}
+
+#[test]
+fn boards_start_identical_side_by_side() {
+    let app = app();
+
+    assert_eq!(forms(&app, Player::One), forms(&app, Player::Two));
+    assert!(
+        board_of(&app, Player::One).bottom_left().x < board_of(&app, Player::Two).bottom_left().x
+    );
+}
+
+#[test]
+fn each_board_scores_for_its_owner() {
+    let mut app = app();
+    // Случайная доска первого игрока могла бы сама сыграть каскадом.
+    settle(&mut app);
+    let still = pattern_board(&app);
+    load_board_of(&mut app, Player::One, still);
+    let untouched = forms(&app, Player::One);
+    let [one, two] = scores(&app);
+
+    play_match(&mut app, Player::Two);
+
+    assert_eq!(scores(&app), [one, two + 3 * SCORE_PER_TILE]);
+    assert_eq!(forms(&app, Player::One), untouched);
+}
+
+#[test]
+fn reaching_target_ends_race() {
+    let mut app = app();
+    app.insert_resource(ScoreStorage::solo(RACE_TARGET_SCORE - SCORE_PER_TILE));
+
+    play_match(&mut app, Player::One);
+
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::GameOver
+    );
+    assert_eq!(
+        app.world().resource::<ScoreStorage>().leader(),
+        Some(Player::One)
+    );
+}
+
+#[test]
+fn first_to_reach_target_wins_when_cascade_overtakes() {
+    let mut app = app();
+    settle(&mut app);
+
+    let mut one = pattern_board(&app);
+    one[0][0] = Form::Circle;
+    one[0][1] = Form::Circle;
+    one[0][3] = Form::Circle;
+    // У второго игрока после ряда кругов кольца падают в нижний ряд и дают каскад.
+    let mut two = one.clone();
+    two[0][2] = Form::Annulus;
+    two[1][1] = Form::Annulus;
+    two[1][2] = Form::Annulus;
+    load_board_of(&mut app, Player::One, one);
+    load_board_of(&mut app, Player::Two, two);
+    app.insert_resource(ScoreStorage::new([
+        RACE_TARGET_SCORE - 3 * SCORE_PER_TILE,
+        RACE_TARGET_SCORE - 4 * SCORE_PER_TILE,
+    ]));
+
+    swap_on(&mut app, Player::One, (0, 2), (0, 3));
+    swap_on(&mut app, Player::Two, (0, 2), (0, 3));
+    settle(&mut app);
+
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::GameOver
+    );
+    let [one, two] = scores(&app);
+    assert_eq!(one, RACE_TARGET_SCORE);
+    assert!(two > one);
+    assert_eq!(
+        app.world().resource::<GameSession>().first_to_target(),
+        Some(Player::One)
+    );
+}
+
+#[test]
+fn autoplay_stays_off_in_race() {
+    let enabled = [GameMode::Classic, GameMode::Race].map(|mode| {
+        let mut app = app_with(|app| {
+            app.add_plugins(AutoplayPlugin)
+                .init_resource::<ButtonInput<KeyCode>>()
+                .insert_resource(GameSession::new(mode, 1.));
+        });
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyA);
+        app.update();
+
+        app.world().resource::<Autoplay>().enabled
+    });
+
+    assert_eq!(enabled, [true, false]);
+}
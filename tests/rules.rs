@@ -0,0 +1,101 @@
+use tile_matching::{
+    board::{BoardLayout, CellKind, Form},
+    rules::{Grid, MatchShape, group_runs},
+};
+
+fn grid(layout: &str) -> Grid {
+    let layout: BoardLayout = layout.parse().unwrap();
+    let mut grid = Grid::new(layout.width(), layout.height(), Form::ALL.len());
+
+    for (row_id, row) in layout.forms().unwrap().into_iter().enumerate() {
+        for (col_id, form) in row.into_iter().enumerate() {
+            grid.set((row_id, col_id).into(), Some(form));
+        }
+    }
+
+    grid
+}
+
+fn shapes(layout: &str) -> Vec<(Form, MatchShape, usize)> {
+    let mut shapes: Vec<_> = group_runs(&grid(layout).find_runs())
+        .into_iter()
+        .map(|found| (found.form, found.shape, found.cells.len()))
+        .collect();
+    shapes.sort_by_key(|(_, shape, len)| (*shape, *len));
+    shapes
+}
+
+#[test]
+fn separate_runs_stay_separate() {
+    assert_eq!(
+        shapes(
+            "
+            C S T R A
+            C T R A S
+            C R A S S
+            T A S R R
+            R R R C T
+            "
+        ),
+        [
+            (Form::Rhombus, MatchShape::Line, 3),
+            (Form::Circle, MatchShape::Line, 3),
+        ]
+    );
+}
+
+#[test]
+fn crossing_runs_form_shapes() {
+    assert_eq!(
+        shapes(
+            "
+            C S T R A
+            C T R A S
+            C C C S T
+            T A S R A
+            R T A C T
+            "
+        ),
+        [(Form::Circle, MatchShape::L, 5)]
+    );
+    assert_eq!(
+        shapes(
+            "
+            S C T R A
+            A C R A S
+            C C C S T
+            T A S R A
+            R T A C T
+            "
+        ),
+        [(Form::Circle, MatchShape::T, 5)]
+    );
+    assert_eq!(
+        shapes(
+            "
+            S T C R A
+            A R C A S
+            C C C C T
+            T A C R A
+            R T A C T
+            "
+        ),
+        [(Form::Circle, MatchShape::Cross, 7)]
+    );
+}
+
+#[test]
+fn blockers_break_runs_and_tiles_fall_past_holes() {
+    let mut row = grid("C C C");
+    row.set_kind((0, 1).into(), CellKind::Blocker);
+    assert!(row.find_runs().is_empty());
+
+    let mut column = grid("S\nC\nC");
+    column.set_kind((1, 0).into(), CellKind::Hole);
+    column.set((0, 0).into(), None);
+    column.collapse();
+
+    assert_eq!(column.get((0, 0).into()), Some(Form::Square));
+    assert_eq!(column.get((1, 0).into()), None);
+    assert_eq!(column.get((2, 0).into()), None);
+}
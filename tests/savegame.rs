@@ -0,0 +1,117 @@
+mod common;
+
+use std::{env, fs, path::PathBuf};
+
+use bevy::prelude::*;
+use tile_matching::{
+    AppState, GameMode, GameSession, GameState,
+    board::Form,
+    campaign::CampaignProgress,
+    editor::LevelEditor,
+    level::Level,
+    savegame::{ContinueSlot, SaveGamePlugin},
+    storage::StorageRoot,
+};
+
+use common::{app_with, load_board, pattern_board, settle, swap};
+
+/// Сохранение пишется в каталог данных, поэтому каждый тест подменяет его своим.
+fn data_dir(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("tile-matching-{test}-{}", std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+
+    dir
+}
+
+fn level(moves: u32) -> Level {
+    Level {
+        name: "Test".to_string(),
+        layout: "...".parse().unwrap(),
+        moves: Some(moves),
+        goals: Vec::new(),
+        star_scores: Vec::new(),
+    }
+}
+
+#[test]
+fn editor_test_play_keeps_saved_game() {
+    let dir = data_dir("editor-test-play");
+    let save = dir.join("savegame.ron");
+
+    let mut app = app_with(|app| {
+        app.insert_resource(StorageRoot::new(&dir))
+            .add_plugins(SaveGamePlugin)
+            .insert_resource(GameSession::new(GameMode::Classic, 1.));
+    });
+    settle(&mut app);
+    play_match(&mut app);
+    let saved = fs::read_to_string(&save).unwrap();
+
+    let editor = LevelEditor::new(level(1));
+    app.insert_resource(editor.test_session(1.))
+        .insert_resource(editor);
+    for state in [AppState::Editor, AppState::InGame] {
+        app.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(state);
+        app.update();
+    }
+    settle(&mut app);
+
+    // Единственный ход уровня кончает пробную игру.
+    play_match(&mut app);
+
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::GameOver
+    );
+    let after = fs::read_to_string(&save);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(after.unwrap(), saved);
+}
+
+#[test]
+fn campaign_progress_outlives_finished_game() {
+    let dir = data_dir("campaign-progress");
+
+    let mut app = app_with(|app| {
+        app.insert_resource(StorageRoot::new(&dir))
+            .add_plugins(SaveGamePlugin)
+            .insert_resource(GameSession::new(GameMode::Classic, 1.).with_level(level(1)));
+    });
+    settle(&mut app);
+    app.world_mut()
+        .resource_mut::<CampaignProgress>()
+        .record("levels/one.level.ron", 2);
+    app.update();
+    play_match(&mut app);
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::GameOver
+    );
+
+    // Законченная партия уходит из сохранения, а звёзды кампании остаются.
+    let app = app_with(|app| {
+        app.insert_resource(StorageRoot::new(&dir))
+            .add_plugins(SaveGamePlugin);
+    });
+    let continued = app.world().resource::<ContinueSlot>().0.is_some();
+    let stars = app
+        .world()
+        .resource::<CampaignProgress>()
+        .stars("levels/one.level.ron");
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!continued);
+    assert_eq!(stars, 2);
+}
+
+fn play_match(app: &mut App) {
+    let mut forms = pattern_board(app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+    load_board(app, forms);
+    swap(app, (0, 2), (0, 3));
+    settle(app);
+}
@@ -0,0 +1,105 @@
+use std::fs;
+
+use tile_matching::{accessibility::Palette, locale::Language, settings::Settings};
+
+#[test]
+fn settings_round_trip_through_toml() {
+    let mut settings = Settings {
+        language: Language::Russian,
+        theme: "High contrast".to_string(),
+        fullscreen: true,
+        vsync: false,
+        hint_delay_secs: 5.,
+        effects: false,
+        ..Default::default()
+    };
+    settings.next_animation_speed();
+    settings.audio.music = 0.2;
+    settings.accessibility.palette = Palette::Protanopia;
+    settings.accessibility.form_cues = true;
+
+    let source = toml::to_string_pretty(&settings).unwrap();
+    assert_eq!(toml::from_str::<Settings>(&source).unwrap(), settings);
+}
+
+#[test]
+fn missing_fields_take_defaults() {
+    let settings = toml::from_str::<Settings>(
+        r#"
+        language = "Russian"
+
+        [audio]
+        music = 0.1
+        "#,
+    )
+    .unwrap();
+
+    let defaults = Settings::default();
+    assert_eq!(settings.language, Language::Russian);
+    assert_eq!(settings.audio.music, 0.1);
+    assert_eq!(settings.audio.master, defaults.audio.master);
+    assert_eq!(settings.theme, defaults.theme);
+    assert_eq!(settings.accessibility, defaults.accessibility);
+}
+
+#[test]
+fn hand_edited_values_are_clamped() {
+    let settings = Settings {
+        animation_speed: 0.,
+        hint_delay_secs: -3.,
+        ..Default::default()
+    }
+    .sanitized();
+
+    assert_eq!(settings.animation_speed, 1.);
+    assert_eq!(settings.hint_delay_secs, 0.);
+}
+
+#[test]
+fn option_steps_wrap_around() {
+    let mut settings = Settings::default();
+    let mut speeds = Vec::new();
+    for _ in 0..5 {
+        settings.next_animation_speed();
+        speeds.push(settings.animation_speed);
+    }
+    assert_eq!(speeds, [1.5, 2., 0.5, 0.75, 1.]);
+
+    settings.hint_delay_secs = 7.;
+    settings.next_hint_delay();
+    assert_eq!(
+        settings.hint_delay_secs, 0.,
+        "Unknown delay restarts the list"
+    );
+    settings.next_hint_delay();
+    assert_eq!(settings.hint_delay_secs, 3.);
+}
+
+#[test]
+fn previous_version_files_are_imported() {
+    let dir = std::env::temp_dir().join(format!("tile-matching-settings-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    assert_eq!(Settings::from_legacy_files(&dir), None);
+
+    for (file, content) in [
+        ("audio.ron", "(master: 0.3, music: 0.1, effects: 0.6)"),
+        ("effects.ron", "(enabled: false)"),
+        ("theme.ron", r#"(theme: "Gems")"#),
+        ("accessibility.ron", "(palette: Deuteranopia, glyphs: true)"),
+        ("locale.ron", "(language: Russian)"),
+    ] {
+        fs::write(dir.join(file), content).unwrap();
+    }
+    let settings = Settings::from_legacy_files(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let settings = settings.unwrap();
+    assert_eq!(settings.audio.master, 0.3);
+    assert_eq!(settings.audio.music, 0.1);
+    assert!(!settings.effects);
+    assert_eq!(settings.theme, "Gems");
+    assert_eq!(settings.accessibility.palette, Palette::Deuteranopia);
+    assert!(settings.accessibility.glyphs);
+    assert_eq!(settings.language, Language::Russian);
+    assert_eq!(settings.vsync, Settings::default().vsync);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use tile_matching::{
+    board::Form,
+    theme::{ActiveTheme, FormLook, Theme},
+};
+
+use common::{app, board};
+
+const ASSETS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
+
+fn themes() -> Vec<Theme> {
+    let mut themes = Vec::new();
+
+    for entry in fs::read_dir(Path::new(ASSETS).join("themes")).unwrap() {
+        let path = entry.unwrap().path();
+        if !path.to_string_lossy().ends_with(".theme.ron") {
+            continue;
+        }
+
+        let source = fs::read_to_string(&path).unwrap();
+        let theme = ron::from_str::<Theme>(&source)
+            .unwrap_or_else(|err| panic!("{} does not parse: {err}", path.display()));
+        themes.push(theme);
+    }
+
+    themes
+}
+
+#[test]
+fn bundled_themes_parse_and_reference_existing_files() {
+    let themes = themes();
+    assert!(themes.len() >= 3);
+    assert!(
+        themes
+            .iter()
+            .any(|theme| theme.name == Theme::default().name)
+    );
+
+    for theme in &themes {
+        let sprites = Form::ALL
+            .iter()
+            .filter_map(|form| match &theme.forms.get(*form).look {
+                FormLook::Sprite(path) => Some(path),
+                FormLook::Shape(_) => None,
+            });
+
+        for path in sprites.chain(&theme.font) {
+            assert!(
+                Path::new(ASSETS).join(path).exists(),
+                "Theme {:?} references missing {path}",
+                theme.name
+            );
+        }
+    }
+}
+
+#[test]
+fn switching_theme_restyles_tiles() {
+    let mut app = app();
+    let theme = themes()
+        .into_iter()
+        .find(|theme| theme.name == "High contrast")
+        .unwrap();
+    app.insert_resource(ActiveTheme(theme.clone()));
+    app.update();
+
+    let tiles = board(&app)
+        .into_iter()
+        .flatten()
+        .filter_map(|cell| cell.tile)
+        .collect::<Vec<_>>();
+    assert!(!tiles.is_empty());
+
+    let world = app.world();
+    let materials = world.resource::<Assets<ColorMaterial>>();
+    for tile in tiles {
+        let material = world
+            .get::<MeshMaterial2d<ColorMaterial>>(tile.visual_entity)
+            .unwrap();
+
+        assert_eq!(
+            materials.get(&material.0).unwrap().color,
+            theme.forms.get(tile.form).color.0
+        );
+    }
+}
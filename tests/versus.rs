@@ -0,0 +1,120 @@
+mod common;
+
+use bevy::prelude::*;
+use tile_matching::{
+    GameMode, GameSession, GameState, ScoreStorage,
+    board::Form,
+    rules::SCORE_PER_TILE,
+    versus::{Player, VERSUS_ROUNDS, Versus},
+};
+
+use common::{app_with, load_board, pattern_board, settle, swap};
+
+fn app() -> App {
+    app_with(|app| {
+        app.insert_resource(GameSession::new(GameMode::Versus, 1.));
+    })
+}
+
+fn versus(app: &App) -> Versus {
+    *app.world().resource::<GameSession>().versus().unwrap()
+}
+
+fn scores(app: &App) -> [usize; 2] {
+    let score = app.world().resource::<ScoreStorage>();
+
+    Player::ALL.map(|player| score.of(player))
+}
+
+/// Загружает доску, где обмен собирает в нижнем ряду `run` кругов подряд, три или четыре, и делает его.
+fn play_match(app: &mut App, run: usize) {
+    let mut forms = pattern_board(app);
+    forms[0][0] = Form::Circle;
+    forms[0][1] = Form::Circle;
+    forms[0][3] = Form::Circle;
+
+    if run == 4 {
+        // Недостающий круг приезжает сверху и замыкает C C _ C.
+        forms[0][4] = Form::Square;
+        forms[1][2] = Form::Circle;
+        load_board(app, forms);
+        swap(app, (0, 2), (1, 2));
+    } else {
+        load_board(app, forms);
+        swap(app, (0, 2), (0, 3));
+    }
+    settle(app);
+}
+
+#[test]
+fn turns_alternate_and_points_go_to_mover() {
+    let mut app = app();
+    assert_eq!(versus(&app).player(), Player::One);
+
+    play_match(&mut app, 3);
+    assert_eq!(scores(&app), [3 * SCORE_PER_TILE, 0]);
+    assert_eq!(versus(&app).player(), Player::Two);
+    assert_eq!(versus(&app).round(), 1);
+
+    // Обмен без совпадения ходом не считается.
+    swap(&mut app, (5, 5), (5, 6));
+    settle(&mut app);
+    assert_eq!(versus(&app).player(), Player::Two);
+
+    play_match(&mut app, 3);
+    assert_eq!(scores(&app), [3 * SCORE_PER_TILE, 3 * SCORE_PER_TILE]);
+    assert_eq!(versus(&app).player(), Player::One);
+    assert_eq!(versus(&app).round(), 2);
+}
+
+#[test]
+fn big_match_grants_extra_turn() {
+    let mut app = app();
+
+    play_match(&mut app, 4);
+    assert_eq!(scores(&app), [4 * SCORE_PER_TILE, 0]);
+    assert_eq!(versus(&app).player(), Player::One);
+}
+
+#[test]
+fn start_cascades_do_not_grant_extra_turn() {
+    let mut app = app();
+    settle(&mut app);
+
+    // Четыре круга лежат на доске до первого хода и сгорают сами.
+    let mut forms = pattern_board(&app);
+    for form in &mut forms[0][..4] {
+        *form = Form::Circle;
+    }
+    load_board(&mut app, forms);
+    settle(&mut app);
+    assert_eq!(versus(&app).player(), Player::One);
+
+    play_match(&mut app, 3);
+    assert_eq!(versus(&app).player(), Player::Two);
+}
+
+#[test]
+fn game_ends_after_last_round() {
+    let mut app = app();
+
+    for _ in 0..2 * VERSUS_ROUNDS {
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Playing
+        );
+        play_match(&mut app, 3);
+    }
+
+    assert!(versus(&app).is_over());
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::GameOver
+    );
+    let score = app.world().resource::<ScoreStorage>();
+    assert_eq!(score.leader(), None);
+    assert_eq!(
+        score.total(),
+        2 * VERSUS_ROUNDS as usize * 3 * SCORE_PER_TILE
+    );
+}